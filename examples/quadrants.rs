@@ -1,5 +1,5 @@
 use futures::executor::block_on;
-use sdl_wrapper::{Event, Keycode, ScreenContextManager};
+use sdl_wrapper::{Key, ScreenContextManager, ScreenEvent};
 use std::time::SystemTime;
 
 /// Height of the window
@@ -74,13 +74,13 @@ async fn screen_loop(mut screen: ScreenContextManager) {
         for event in screen.get_events() {
             match event {
                 // Salirse del programa si se cierra la ventana o estripa Esc
-                Event::Quit { .. } => break 'main,
-                Event::KeyDown {
-                    keycode: Some(key), ..
+                ScreenEvent::Quit => break 'main,
+                ScreenEvent::Key {
+                    key, pressed: true, ..
                 } => match key {
-                    Keycode::Escape => break 'main,
-                    Keycode::M => red = 1.0,
-                    Keycode::N => red = 0.2,
+                    Key::Escape => break 'main,
+                    Key::M => red = 1.0,
+                    Key::N => red = 0.2,
                     _ => (),
                 },
                 _ => (),
@@ -0,0 +1,159 @@
+//! This module provides flipbook animations, sequences of images shown one after the other at a
+//! steady rate.
+
+use crate::{Image, LoadImageError, SpriteSheet};
+use std::path::Path;
+use std::time::Duration;
+
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+/// A sequence of frames, each shown for the same duration. The animation is advanced by the time
+/// that passed, and the frame to draw is then read with
+/// [`current_frame`](Animation::current_frame).
+#[derive(Clone, Debug)]
+pub struct Animation {
+    frames: Vec<Image>,
+    frame_duration: Duration,
+    looping: bool,
+    elapsed: Duration,
+}
+
+impl Animation {
+    /// Creates an animation showing every frame for `frame_duration`.
+    pub fn new(frames: Vec<Image>, frame_duration: Duration) -> Animation {
+        Animation {
+            frames,
+            frame_duration,
+            looping: false,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Creates an animation from all the tiles of `sheet`, in order.
+    pub fn from_sheet(sheet: &SpriteSheet, frame_duration: Duration) -> Animation {
+        let frames = (0..sheet.get_tile_count())
+            .filter_map(|index| sheet.get_tile_image(index))
+            .collect();
+        Animation::new(frames, frame_duration)
+    }
+
+    /// Creates an animation from the images at `paths`, in order.
+    pub fn load<P: AsRef<Path>>(
+        paths: &[P],
+        frame_duration: Duration,
+    ) -> Result<Animation, LoadImageError> {
+        let frames = paths.iter().map(Image::load).collect::<Result<_, _>>()?;
+        Ok(Animation::new(frames, frame_duration))
+    }
+
+    /// Makes the animation start over after its last frame instead of stopping on it.
+    pub fn looping(mut self, looping: bool) -> Animation {
+        self.looping = looping;
+        self
+    }
+
+    /// Moves the animation forward by `elapsed`, typically the time since the last frame. The
+    /// time an animation that doesn't loop has been running stops growing at [`Duration::MAX`].
+    pub fn advance(&mut self, elapsed: Duration) {
+        self.elapsed = self.elapsed.saturating_add(elapsed);
+        let duration = self.get_duration();
+        if self.looping && !duration.is_zero() {
+            let nanos = self.elapsed.as_nanos() % duration.as_nanos();
+            self.elapsed = Duration::new(
+                (nanos / NANOS_PER_SECOND) as u64,
+                (nanos % NANOS_PER_SECOND) as u32,
+            );
+        }
+    }
+
+    /// Goes back to the first frame.
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+    }
+
+    /// Returns how long it takes to go through all the frames once, at most [`Duration::MAX`].
+    pub fn get_duration(&self) -> Duration {
+        let count = self.frames.len().min(u32::MAX as usize) as u32;
+        self.frame_duration.saturating_mul(count)
+    }
+
+    /// Returns the index of the frame to show, `None` without frames.
+    pub fn get_frame_index(&self) -> Option<usize> {
+        let last = self.frames.len().checked_sub(1)?;
+        if self.frame_duration.is_zero() {
+            return Some(last);
+        }
+        let index = self.elapsed.as_nanos() / self.frame_duration.as_nanos();
+        Some(index.min(last as u128) as usize)
+    }
+
+    /// Returns the frame to show, e.g. to draw with
+    /// [`blit_image`](crate::ScreenContextManager::blit_image). `None` without frames.
+    pub fn current_frame(&self) -> Option<&Image> {
+        self.get_frame_index().map(|index| &self.frames[index])
+    }
+
+    /// Returns whether an animation that doesn't loop has reached the end of its last frame.
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.elapsed >= self.get_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animation(frames: usize, frame_duration: Duration) -> Animation {
+        let frame = Image::from_pixels(image::RgbaImage::new(1, 1));
+        Animation::new(vec![frame; frames], frame_duration)
+    }
+
+    #[test]
+    fn frames_follow_the_elapsed_time() {
+        let mut animation = animation(3, Duration::from_millis(100));
+        assert_eq!(animation.get_frame_index(), Some(0));
+        animation.advance(Duration::from_millis(150));
+        assert_eq!(animation.get_frame_index(), Some(1));
+        animation.advance(Duration::from_millis(1000));
+        assert_eq!(animation.get_frame_index(), Some(2));
+        assert!(animation.is_finished());
+    }
+
+    #[test]
+    fn looping_animations_wrap_around() {
+        let mut animation = animation(3, Duration::from_millis(100)).looping(true);
+        animation.advance(Duration::from_millis(350));
+        assert_eq!(animation.get_frame_index(), Some(0));
+        animation.advance(Duration::from_millis(200));
+        assert_eq!(animation.get_frame_index(), Some(2));
+        assert!(!animation.is_finished());
+    }
+
+    #[test]
+    fn empty_and_instant_animations() {
+        assert_eq!(
+            animation(0, Duration::from_millis(100)).get_frame_index(),
+            None
+        );
+        assert_eq!(animation(4, Duration::ZERO).get_frame_index(), Some(3));
+    }
+
+    #[test]
+    fn long_durations_saturate() {
+        let mut animation = animation(3, Duration::MAX / 2);
+        assert_eq!(animation.get_duration(), Duration::MAX);
+        animation.advance(Duration::MAX);
+        animation.advance(Duration::MAX);
+        assert_eq!(animation.get_frame_index(), Some(2));
+        assert!(animation.is_finished());
+    }
+
+    #[test]
+    fn long_looping_animations_wrap_around() {
+        let mut animation = animation(2, Duration::MAX / 2).looping(true);
+        animation.advance(Duration::MAX / 2 + Duration::from_secs(1));
+        assert_eq!(animation.get_frame_index(), Some(1));
+        animation.advance(Duration::MAX / 2);
+        assert_eq!(animation.get_frame_index(), Some(0));
+    }
+}
@@ -0,0 +1,237 @@
+//! This module connects audio to the render loop, so visualizers can draw what is being played.
+
+use crate::{events, ScreenContextManager, ScreenEvent};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Keeps the most recent audio samples handed to it, shared between an audio callback that
+/// pushes samples and the render loop that reads them. Cloning it yields another handle to the
+/// same samples.
+#[derive(Clone)]
+pub struct AudioTap {
+    samples: Arc<Mutex<VecDeque<f32>>>,
+    capacity: usize,
+}
+
+impl AudioTap {
+    /// Creates a tap remembering the last `capacity` samples, rounded up to a power of two so
+    /// that the spectrum can be computed from all of them.
+    pub fn new(capacity: usize) -> AudioTap {
+        let capacity = capacity.max(2).next_power_of_two();
+        AudioTap {
+            samples: Arc::new(Mutex::new(VecDeque::from(vec![0.0; capacity]))),
+            capacity,
+        }
+    }
+
+    /// Adds mono samples in the range [-1, 1] that were just played. Meant to be called from the
+    /// audio callback; multi-channel audio should be mixed down first.
+    pub fn push(&self, samples: &[f32]) {
+        let mut buffer = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        let skip = samples.len().saturating_sub(self.capacity);
+        for &sample in &samples[skip..] {
+            buffer.pop_front();
+            buffer.push_back(sample);
+        }
+    }
+
+    /// Copies the most recent samples into `out`, oldest first.
+    pub fn latest(&self, out: &mut Vec<f32>) {
+        let buffer = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        out.clear();
+        out.extend(buffer.iter());
+    }
+}
+
+/// Magnitudes of the frequency bins of `samples` (whose length must be a power of two), from 0Hz
+/// up to half the sample rate. A Hann window is applied first to reduce leakage between bins.
+pub fn spectrum(samples: &[f32], out: &mut Vec<f32>) {
+    let n = samples.len();
+    debug_assert!(n.is_power_of_two());
+
+    let mut re: Vec<f32> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            let hann = 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / n as f32).cos();
+            sample * hann
+        })
+        .collect();
+    let mut im = vec![0.0; n];
+
+    // Iterative radix-2 FFT: bit-reversal permutation, then butterflies of growing size
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            re.swap(i, j);
+        }
+    }
+    let mut size = 2;
+    while size <= n {
+        let angle = -std::f32::consts::TAU / size as f32;
+        for start in (0..n).step_by(size) {
+            for k in 0..size / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + size / 2);
+                let twiddled_re = re[b] * cos - im[b] * sin;
+                let twiddled_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - twiddled_re;
+                im[b] = im[a] - twiddled_im;
+                re[a] += twiddled_re;
+                im[a] += twiddled_im;
+            }
+        }
+        size *= 2;
+    }
+
+    out.clear();
+    out.extend((0..n / 2).map(|i| (re[i] * re[i] + im[i] * im[i]).sqrt() * 2.0 / n as f32));
+}
+
+/// Number of past frames the beat detector averages the energy over.
+const BEAT_HISTORY: usize = 60;
+
+/// Number of beat and level events kept until they are polled, older ones are dropped first.
+const MAX_AUDIO_EVENTS: usize = 64;
+
+/// Detects beats and level crossings in the audio snapshotted every frame.
+#[derive(Default)]
+pub(crate) struct AudioTriggers {
+    beat_sensitivity: Option<f32>,
+    level_threshold: Option<f32>,
+    history: VecDeque<f32>,
+    in_beat: bool,
+    above: bool,
+}
+
+impl AudioTriggers {
+    /// Looks at this frame's samples, queueing the events they trigger with the given timestamp.
+    fn detect(
+        &mut self,
+        samples: &[f32],
+        timestamp: Duration,
+        events: &mut VecDeque<(Duration, ScreenEvent)>,
+    ) {
+        if samples.is_empty() {
+            return;
+        }
+        let energy = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+
+        if let Some(sensitivity) = self.beat_sensitivity {
+            // Silence averages out to nearly nothing, keep noise from counting as beats
+            let average =
+                (self.history.iter().sum::<f32>() / self.history.len().max(1) as f32).max(1e-4);
+            let is_beat = self.history.len() == BEAT_HISTORY && energy > average * sensitivity;
+            if is_beat && !self.in_beat {
+                let strength = energy / average;
+                queue(events, (timestamp, ScreenEvent::Beat { strength }));
+            }
+            self.in_beat = is_beat;
+            if self.history.len() == BEAT_HISTORY {
+                self.history.pop_front();
+            }
+            self.history.push_back(energy);
+        }
+
+        if let Some(threshold) = self.level_threshold {
+            let level = energy.sqrt();
+            let above = level > threshold;
+            if above != self.above {
+                queue(
+                    events,
+                    (timestamp, ScreenEvent::AudioLevel { level, above }),
+                );
+            }
+            self.above = above;
+        }
+    }
+}
+
+/// Queues an audio event, dropping the oldest one if the queue is full because events aren't
+/// being polled.
+fn queue(events: &mut VecDeque<(Duration, ScreenEvent)>, event: (Duration, ScreenEvent)) {
+    if events.len() == MAX_AUDIO_EVENTS {
+        events.pop_front();
+    }
+    events.push_back(event);
+}
+
+impl ScreenContextManager {
+    /// Attaches a tap whose samples are snapshotted every time a frame is presented, so that the
+    /// whole next frame sees the same audio through
+    /// [`get_audio_samples`](ScreenContextManager::get_audio_samples) and
+    /// [`get_audio_spectrum`](ScreenContextManager::get_audio_spectrum). Everything played by
+    /// this crate, like [`beep`](ScreenContextManager::beep), is fed into the tap.
+    pub fn set_audio_tap(&mut self, tap: Option<AudioTap>) {
+        if let Some(speaker) = self.speaker.as_mut() {
+            speaker.set_tap(tap.clone());
+        }
+        self.audio_tap = tap;
+        self.audio_samples.clear();
+        self.audio_spectrum.clear();
+    }
+
+    /// Returns the audio samples snapshotted when the last frame was presented, oldest first.
+    pub fn get_audio_samples(&self) -> &[f32] {
+        &self.audio_samples
+    }
+
+    /// Returns the magnitude spectrum of [`get_audio_samples`](Self::get_audio_samples), from 0Hz
+    /// up to half the sample rate in evenly spaced bins.
+    pub fn get_audio_spectrum(&self) -> &[f32] {
+        &self.audio_spectrum
+    }
+
+    /// Reports a [`ScreenEvent::Beat`] whenever the energy of the attached tap's samples rises
+    /// above `sensitivity` times its average over the last second or so (1.3 to 1.5 work well for
+    /// most music), or stops detecting beats with `None`. Only the latest 64 beat and level
+    /// events are kept while events aren't polled.
+    pub fn set_beat_detection(&mut self, sensitivity: Option<f32>) {
+        self.audio_triggers.beat_sensitivity = sensitivity;
+        self.audio_triggers.history.clear();
+        self.audio_triggers.in_beat = false;
+    }
+
+    /// Reports a [`ScreenEvent::AudioLevel`] whenever the RMS level of the attached tap's samples
+    /// crosses `threshold` in either direction, or stops watching the level with `None`. Only the
+    /// latest 64 beat and level events are kept while events aren't polled.
+    pub fn set_level_threshold(&mut self, threshold: Option<f32>) {
+        self.audio_triggers.level_threshold = threshold;
+        self.audio_triggers.above = false;
+    }
+
+    /// Takes this frame's snapshot of the attached tap, queueing the events it triggers.
+    pub(crate) fn snapshot_audio(&mut self) {
+        if let Some(tap) = self.audio_tap.as_ref() {
+            tap.latest(&mut self.audio_samples);
+            spectrum(&self.audio_samples, &mut self.audio_spectrum);
+            self.audio_triggers.detect(
+                &self.audio_samples,
+                events::ticks(),
+                &mut self.audio_events,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpolled_events_are_capped() {
+        let mut triggers = AudioTriggers {
+            level_threshold: Some(0.5),
+            ..AudioTriggers::default()
+        };
+        let mut events = VecDeque::new();
+        for frame in 0..200u64 {
+            let sample = if frame % 2 == 0 { 1.0 } else { 0.0 };
+            triggers.detect(&[sample; 16], Duration::from_millis(frame), &mut events);
+        }
+        assert_eq!(events.len(), MAX_AUDIO_EVENTS);
+        assert_eq!(events.back().unwrap().0, Duration::from_millis(199));
+    }
+}
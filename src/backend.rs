@@ -0,0 +1,267 @@
+//! This module defines where presented frames go, so that something other than the SDL window
+//! can receive them. Drawing itself always happens in the in-memory framebuffer; backends only
+//! get the finished frames, and notifications of the drawing calls for logging them.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::{
+    output::Output, Color, FillRule, InitError, PresentationError, ScreenContextManager,
+    SdlContext, StrokeStyle, WindowOptions,
+};
+
+/// Receives every presented frame, after overlays and filters were applied. Frames go to the
+/// window by default; setting another backend with
+/// [`set_backend`](ScreenContextManager::set_backend) sends them elsewhere, e.g. nowhere for
+/// headless rendering or into a log for tests.
+///
+/// A backend is a presentation sink, not a drawing surface: the screen rasterizes everything into
+/// its own framebuffer whatever the backend, and the notification methods below are only told
+/// what was drawn, so a backend can't draw shapes in its own way.
+pub trait Backend {
+    /// Shows a `width`×`height` frame. `region` is the only area that changed since the last
+    /// frame as (x, y, width, height), when known.
+    fn present(
+        &mut self,
+        pixels: &[Color],
+        width: u32,
+        height: u32,
+        region: Option<(u32, u32, u32, u32)>,
+    ) -> Result<(), PresentationError>;
+
+    /// Told about [`set_color`](ScreenContextManager::set_color) calls, with the resulting color.
+    fn set_color(&mut self, _color: Color) {}
+
+    /// Told about [`plot_pixel`](ScreenContextManager::plot_pixel) calls. Shapes and other
+    /// higher-level drawing write to the framebuffer directly and are not reported pixel by
+    /// pixel, but through [`draw`](Backend::draw).
+    fn plot_pixel(&mut self, _x: u32, _y: u32) {}
+
+    /// Told about the other drawing calls, like shapes, batches, images and text, with the
+    /// arguments they were made with.
+    fn draw(&mut self, _call: &DrawCall) {}
+
+    /// Told about [`clear`](ScreenContextManager::clear) and
+    /// [`clear_with_rgb`](ScreenContextManager::clear_with_rgb) calls, with the clear color.
+    fn clear(&mut self, _color: Color) {}
+}
+
+/// Drops every frame, for rendering without a window.
+struct Headless;
+
+impl Backend for Headless {
+    fn present(
+        &mut self,
+        _pixels: &[Color],
+        _width: u32,
+        _height: u32,
+        _region: Option<(u32, u32, u32, u32)>,
+    ) -> Result<(), PresentationError> {
+        Ok(())
+    }
+}
+
+/// A drawing call reported to a backend, as a [`MockBackend`] logs it. Coordinates are the
+/// drawing coordinates passed in, before the camera and origin apply.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCall {
+    SetColor(Color),
+    PlotPixel(u32, u32),
+    /// [`plot_pixel_depth`](ScreenContextManager::plot_pixel_depth), with the depth last.
+    PlotPixelDepth(u32, u32, f32),
+    /// [`plot_point_f`](ScreenContextManager::plot_point_f) or
+    /// [`plot_pixel_f`](ScreenContextManager::plot_pixel_f).
+    PlotPoint(f32, f32),
+    /// [`plot_points_colored`](ScreenContextManager::plot_points_colored).
+    PlotPoints(Vec<(f32, f32, Color)>),
+    /// [`stroke_polyline`](ScreenContextManager::stroke_polyline), or
+    /// [`stroke_polygon`](ScreenContextManager::stroke_polygon) when `closed`.
+    Stroke {
+        points: Vec<(f32, f32)>,
+        style: StrokeStyle,
+        closed: bool,
+    },
+    /// [`fill_polygon`](ScreenContextManager::fill_polygon).
+    FillPolygon {
+        points: Vec<(f32, f32)>,
+        rule: FillRule,
+    },
+    /// [`draw_ellipse_aa`](ScreenContextManager::draw_ellipse_aa) or
+    /// [`draw_circle_aa`](ScreenContextManager::draw_circle_aa).
+    Ellipse {
+        center: (f32, f32),
+        radius: (f32, f32),
+    },
+    /// `fill_svg_path` or `stroke_svg_path`, with the path data.
+    SvgPath {
+        data: String,
+        filled: bool,
+    },
+    /// [`Batch::set_color`](crate::Batch::set_color) inside a batch.
+    BatchColor(Color),
+    /// [`Batch::pixel`](crate::Batch::pixel) inside a batch.
+    BatchPixel(i32, i32),
+    /// [`Batch::line`](crate::Batch::line) inside a batch.
+    BatchLine {
+        from: (i32, i32),
+        to: (i32, i32),
+    },
+    /// [`Batch::rect`](crate::Batch::rect) inside a batch.
+    BatchRect {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+    /// An image drawn by [`blit_image`](ScreenContextManager::blit_image),
+    /// [`draw_image`](ScreenContextManager::draw_image) or
+    /// [`blit_image_scaled`](ScreenContextManager::blit_image_scaled), covering
+    /// `width`×`height` pixels.
+    Image {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+    /// [`draw_sprite`](ScreenContextManager::draw_sprite), with the size the sprite covers.
+    Sprite {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+    /// [`blit_surface`](ScreenContextManager::blit_surface).
+    Surface {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+    /// [`draw_tilemap`](ScreenContextManager::draw_tilemap), with the map's scroll position.
+    TileMap {
+        scroll: (i32, i32),
+    },
+    /// [`draw_text`](ScreenContextManager::draw_text), `draw_text_ttf`, or one row of
+    /// [`draw_text_grid`](ScreenContextManager::draw_text_grid).
+    Text {
+        x: i32,
+        y: i32,
+        text: String,
+    },
+    Clear(Color),
+    Present {
+        width: u32,
+        height: u32,
+    },
+}
+
+/// A backend for unit tests that logs the drawing calls made on the screen instead of showing
+/// anything, so that rendering code can be checked without looking at pixels. Clones share the
+/// same log, so keep one to read it after handing another to
+/// [`set_backend`](ScreenContextManager::set_backend).
+///
+/// ```no_run
+/// use sdl_wrapper::{DrawCall, MockBackend, ScreenContextManager};
+///
+/// let mock = MockBackend::new();
+/// let mut screen = ScreenContextManager::headless(8, 8).unwrap();
+/// screen.set_backend(Some(Box::new(mock.clone())));
+/// screen.plot_pixel(1, 2);
+/// assert_eq!(mock.get_calls(), vec![DrawCall::PlotPixel(1, 2)]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MockBackend {
+    calls: Rc<RefCell<Vec<DrawCall>>>,
+}
+
+impl MockBackend {
+    pub fn new() -> MockBackend {
+        MockBackend::default()
+    }
+
+    /// Returns the calls logged so far, oldest first.
+    pub fn get_calls(&self) -> Vec<DrawCall> {
+        self.calls.borrow().clone()
+    }
+
+    /// Empties the log.
+    pub fn clear_calls(&self) {
+        self.calls.borrow_mut().clear();
+    }
+}
+
+impl Backend for MockBackend {
+    fn present(
+        &mut self,
+        _pixels: &[Color],
+        width: u32,
+        height: u32,
+        _region: Option<(u32, u32, u32, u32)>,
+    ) -> Result<(), PresentationError> {
+        let call = DrawCall::Present { width, height };
+        self.calls.borrow_mut().push(call);
+        Ok(())
+    }
+
+    fn set_color(&mut self, color: Color) {
+        self.calls.borrow_mut().push(DrawCall::SetColor(color));
+    }
+
+    fn plot_pixel(&mut self, x: u32, y: u32) {
+        self.calls.borrow_mut().push(DrawCall::PlotPixel(x, y));
+    }
+
+    fn draw(&mut self, call: &DrawCall) {
+        self.calls.borrow_mut().push(call.clone());
+    }
+
+    fn clear(&mut self, color: Color) {
+        self.calls.borrow_mut().push(DrawCall::Clear(color));
+    }
+}
+
+/// Returns the backend frames go to: the one set by the application if any, the window otherwise.
+pub(crate) fn select<'a>(
+    output: &'a mut Output,
+    custom: &'a mut Option<Box<dyn Backend>>,
+) -> &'a mut dyn Backend {
+    match custom {
+        Some(backend) => backend.as_mut(),
+        None => output,
+    }
+}
+
+impl ScreenContextManager {
+    /// Creates a screen that only renders into its framebuffer, for CI or servers without a
+    /// display. SDL runs on its dummy video driver unless the `SDL_VIDEODRIVER` environment
+    /// variable asks for another one, and presenting does nothing besides the usual
+    /// bookkeeping, while drawing, [`save_img`](ScreenContextManager::save_img) and recording
+    /// work as usual.
+    pub fn headless(width: u32, height: u32) -> Result<ScreenContextManager, InitError> {
+        if std::env::var_os("SDL_VIDEODRIVER").is_none() {
+            sdl2::hint::set("SDL_VIDEODRIVER", "dummy");
+        }
+        let context = SdlContext::new()?;
+        let options = WindowOptions::default();
+        let mut screen = Self::create(&context, "headless", width, height, options, true)?;
+        screen.set_backend(Some(Box::new(Headless)));
+        Ok(screen)
+    }
+
+    /// Sends the presented frames to `backend` instead of the window, or back to the window with
+    /// `None`. Window and input methods keep working on the window either way.
+    pub fn set_backend(&mut self, backend: Option<Box<dyn Backend>>) {
+        self.backend = backend;
+    }
+
+    /// Tells the backend set by the application, if any, about a drawing call. The call is only
+    /// built when there is one to tell.
+    pub(crate) fn report<F>(&mut self, call: F)
+    where
+        F: FnOnce() -> DrawCall,
+    {
+        if let Some(backend) = self.backend.as_mut() {
+            backend.draw(&call());
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! This module batches many small primitives together, so they can be drawn in one pass over the
+//! framebuffer instead of one call at a time.
+
+use crate::{
+    camera::Camera, clip::ClipRect, Color, ColorSpace, DrawCall, Origin, ScreenContextManager,
+};
+use std::ops::Range;
+
+/// A horizontal run of pixels on one row, `start` inclusive and `end` exclusive.
+pub(crate) struct Span {
+    row: u32,
+    start: u32,
+    end: u32,
+    color: Color,
+}
+
+/// Collects drawing commands inside [`draw_batch`](ScreenContextManager::draw_batch). Everything
+/// is clipped to the framebuffer and clip rectangle, so coordinates may lie outside of them.
+pub struct Batch {
+    spans: Vec<Span>,
+    color: Color,
+    color_space: ColorSpace,
+    clip: ClipRect,
+    camera: Camera,
+    /// The bottom row when the origin is at the bottom-left, to mirror rows around.
+    bottom_row: Option<i64>,
+    /// The commands to report to the screen's backend, only kept when it has one.
+    calls: Option<Vec<DrawCall>>,
+}
+
+impl Batch {
+    /// Sets the color of the following commands, without affecting the screen's own color.
+    /// Parameters correspond to RGB colors and must be real numbers in the range [0, 1].
+    pub fn set_color(&mut self, r: f32, g: f32, b: f32) {
+        self.color = self.color_space.color(r, g, b);
+        self.log(DrawCall::BatchColor(self.color));
+    }
+
+    /// Plots a single pixel.
+    pub fn pixel(&mut self, x: i32, y: i32) {
+        self.log(DrawCall::BatchPixel(x, y));
+        self.fill_rect(x, y, 1, 1);
+    }
+
+    /// Draws a one pixel wide line between both end points, which are included. Through a
+    /// zooming [camera](ScreenContextManager::set_camera) the end points move, but the line stays
+    /// one pixel wide.
+    pub fn line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        self.log(DrawCall::BatchLine {
+            from: (x0, y0),
+            to: (x1, y1),
+        });
+        let from = self.locate_point(x0, y0);
+        let to = self.locate_point(x1, y1);
+        let ((x0, y0), (x1, y1)) = match self.clip_line(from, to) {
+            Some(visible) => visible,
+            None => return,
+        };
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (step_x, step_y) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let mut error = dx + dy;
+        let (mut x, mut y) = (x0, y0);
+        // Consecutive pixels on the same row are merged into a single span
+        let mut run_start = x;
+
+        loop {
+            let at_end = x == x1 && y == y1;
+            let mut next = (x, y);
+            if !at_end {
+                let doubled = 2 * error;
+                if doubled >= dy {
+                    error += dy;
+                    next.0 += step_x;
+                }
+                if doubled <= dx {
+                    error += dx;
+                    next.1 += step_y;
+                }
+            }
+            if at_end || next.1 != y {
+                self.span(y, run_start.min(x), run_start.max(x) + 1);
+                run_start = next.0;
+            }
+            if at_end {
+                return;
+            }
+            (x, y) = next;
+        }
+    }
+
+    /// Fills a `width`×`height` rectangle whose top-left corner is at (`x`, `y`).
+    pub fn rect(&mut self, x: i32, y: i32, width: u32, height: u32) {
+        self.log(DrawCall::BatchRect {
+            x,
+            y,
+            width,
+            height,
+        });
+        self.fill_rect(x, y, width, height);
+    }
+
+    /// Keeps `call` for the screen's backend, if it has one.
+    fn log(&mut self, call: DrawCall) {
+        if let Some(calls) = self.calls.as_mut() {
+            calls.push(call);
+        }
+    }
+
+    /// Fills the pixels the given rectangle covers through the camera.
+    fn fill_rect(&mut self, x: i32, y: i32, width: u32, height: u32) {
+        let (x, y, width, height) = self.camera.pixel_rect(x as i64, y as i64, width, height);
+        let rows = self.visible_rows();
+        for row in y.max(rows.start)..(y + height as i64).min(rows.end) {
+            self.span(row, x, x + width as i64);
+        }
+    }
+
+    /// Maps a pixel through the camera to the pixel nearest to where its center lands.
+    fn locate_point(&self, x: i32, y: i32) -> (i64, i64) {
+        if self.camera.is_identity() {
+            return (x as i64, y as i64);
+        }
+        let (x, y) = self.camera.world_to_screen((x as f32, y as f32));
+        (x.round() as i64, y.round() as i64)
+    }
+
+    /// Returns the rows that end up inside the clip bounds once mirrored, if they are.
+    fn visible_rows(&self) -> Range<i64> {
+        let (top, bottom) = (self.clip.top as i64, self.clip.bottom as i64);
+        match self.bottom_row {
+            Some(last) => last - bottom + 1..last - top + 1,
+            None => top..bottom,
+        }
+    }
+
+    /// Cuts the line between both end points down to the part crossing the visible pixels, or
+    /// returns `None` if it misses them. The area is grown by a pixel on every side, so that
+    /// rounding the new end points can't lose pixels at its edges.
+    fn clip_line(&self, from: (i64, i64), to: (i64, i64)) -> Option<((i64, i64), (i64, i64))> {
+        let rows = self.visible_rows();
+        let (left, right) = (self.clip.left as f64 - 1.0, self.clip.right as f64);
+        let (top, bottom) = (rows.start as f64 - 1.0, rows.end as f64);
+        let (x, y) = (from.0 as f64, from.1 as f64);
+        let (dx, dy) = ((to.0 - from.0) as f64, (to.1 - from.1) as f64);
+
+        // Liang-Barsky: narrow down the range of the line's parameter inside every edge
+        let (mut enter, mut exit) = (0.0f64, 1.0f64);
+        for (direction, distance) in [
+            (-dx, x - left),
+            (dx, right - x),
+            (-dy, y - top),
+            (dy, bottom - y),
+        ] {
+            if direction == 0.0 {
+                if distance < 0.0 {
+                    return None;
+                }
+            } else if direction < 0.0 {
+                enter = enter.max(distance / direction);
+            } else {
+                exit = exit.min(distance / direction);
+            }
+        }
+        if enter > exit {
+            return None;
+        }
+        let at = |t: f64| ((x + t * dx).round() as i64, (y + t * dy).round() as i64);
+        Some((at(enter), at(exit)))
+    }
+
+    /// Queues the pixels from `start` up to but excluding `end` on `row`, if any are visible.
+    fn span(&mut self, row: i64, start: i64, end: i64) {
+        let row = self.bottom_row.map_or(row, |bottom| bottom - row);
+        if !self.clip.contains_row(row) {
+            return;
+        }
+        let start = start.max(self.clip.left as i64);
+        let end = end.min(self.clip.right as i64);
+        if start < end {
+            self.spans.push(Span {
+                row: row as u32,
+                start: start as u32,
+                end: end as u32,
+                color: self.color,
+            });
+        }
+    }
+}
+
+impl ScreenContextManager {
+    /// Draws everything `commands` adds to the batch at once, starting out with the current
+    /// color. The commands are reordered to go over the framebuffer row by row, which is much
+    /// faster for thousands of small primitives; where they overlap, later commands still end up
+    /// on top.
+    pub fn draw_batch<F>(&mut self, commands: F)
+    where
+        F: FnOnce(&mut Batch),
+    {
+        let mut batch = Batch {
+            spans: std::mem::take(&mut self.batch_spans),
+            color: self.color,
+            color_space: self.color_space,
+            clip: self.clip_bounds(),
+            camera: self.camera,
+            bottom_row: match self.origin {
+                Origin::TopLeft => None,
+                Origin::BottomLeft => Some(self.height as i64 - 1),
+            },
+            calls: self.backend.is_some().then(Vec::new),
+        };
+        commands(&mut batch);
+        for call in batch.calls.take().into_iter().flatten() {
+            self.report(|| call);
+        }
+
+        // A stable sort keeps the order of the spans within each row, which is all that matters
+        // for overlapping ones
+        let mut spans = batch.spans;
+        spans.sort_by_key(|span| span.row);
+        for span in spans.drain(..) {
+            let offset = (span.row * self.width) as usize;
+            self.framebuffer[offset + span.start as usize..offset + span.end as usize]
+                .fill(span.color);
+        }
+        // Keep the allocation around for the next batch
+        self.batch_spans = spans;
+    }
+
+    /// Plots every point in its own color at the pixel nearest to it, later points ending up on
+    /// top of earlier ones. The points go through the [camera](ScreenContextManager::set_camera)
+    /// like other fractional coordinates, and are then sorted into rows so that even hundreds of
+    /// thousands of them are written in one pass over the framebuffer.
+    pub fn plot_points_colored(&mut self, points: &[(f32, f32, Color)]) {
+        self.report(|| DrawCall::PlotPoints(points.to_vec()));
+        let clip = self.clip_bounds();
+        let untransformed = self.is_untransformed();
+        let mut pixels = std::mem::take(&mut self.batch_pixels);
+        pixels.clear();
+        for &(x, y, color) in points {
+            let (x, y) = if untransformed {
+                (x, y)
+            } else {
+                self.world_to_screen(x, y)
+            };
+            let (x, y) = (x.round(), y.round());
+            // Written so that NaN fails every comparison and gets dropped
+            let inside = x >= clip.left as f32
+                && x < clip.right as f32
+                && y >= clip.top as f32
+                && y < clip.bottom as f32;
+            if inside {
+                pixels.push((y as usize * self.width as usize + x as usize, color));
+            }
+        }
+
+        // A stable sort keeps later points after earlier ones on the same pixel
+        pixels.sort_by_key(|&(i, _)| i);
+        for &(i, color) in &pixels {
+            self.framebuffer[i] = color;
+        }
+        // Keep the allocation around for the next call
+        self.batch_pixels = pixels;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A batch drawing into a `width`×`height` framebuffer with the origin at the top-left.
+    fn new_batch(width: u32, height: u32) -> Batch {
+        Batch {
+            spans: Vec::new(),
+            color: Color::new(255, 255, 255),
+            color_space: ColorSpace::Srgb,
+            clip: ClipRect::full(width, height),
+            camera: Camera::default(),
+            bottom_row: None,
+            calls: None,
+        }
+    }
+
+    fn spans(batch: &Batch) -> Vec<(u32, u32, u32)> {
+        let spans = batch.spans.iter();
+        spans.map(|span| (span.row, span.start, span.end)).collect()
+    }
+
+    #[test]
+    fn line_inside_is_rasterized_whole() {
+        let mut batch = new_batch(8, 8);
+        batch.line(0, 0, 3, 1);
+        assert_eq!(spans(&batch), vec![(0, 0, 2), (1, 2, 4)]);
+    }
+
+    #[test]
+    fn far_line_is_clipped_before_rasterizing() {
+        let mut batch = new_batch(10, 10);
+        batch.line(i32::MIN, 5, i32::MAX, 5);
+        assert_eq!(spans(&batch), vec![(5, 0, 10)]);
+
+        let mut batch = new_batch(10, 10);
+        batch.line(-2_000_000_000, -2_000_000_000, 2_000_000_000, 2_000_000_000);
+        let diagonal: Vec<_> = (0..10).map(|i| (i, i, i + 1)).collect();
+        assert_eq!(spans(&batch), diagonal);
+    }
+
+    #[test]
+    fn line_missing_the_screen_draws_nothing() {
+        let mut batch = new_batch(10, 10);
+        batch.line(-100, -5, 100, -1);
+        assert!(spans(&batch).is_empty());
+    }
+
+    #[test]
+    fn far_rect_only_visits_visible_rows() {
+        let mut batch = new_batch(4, 3);
+        batch.rect(-1_000_000_000, -1_000_000_000, u32::MAX, u32::MAX);
+        assert_eq!(spans(&batch), vec![(0, 0, 4), (1, 0, 4), (2, 0, 4)]);
+    }
+
+    #[test]
+    fn rows_are_mirrored_with_a_bottom_left_origin() {
+        let mut batch = new_batch(4, 4);
+        batch.bottom_row = Some(3);
+        batch.rect(1, 0, 2, 2);
+        assert_eq!(spans(&batch), vec![(3, 1, 3), (2, 1, 3)]);
+    }
+}
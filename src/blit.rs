@@ -0,0 +1,262 @@
+//! This module loads images from files and blits them into the framebuffer.
+
+use crate::{Color, Downsampling, DrawCall, LoadImageError, ScaleFilter, ScreenContextManager};
+use std::path::Path;
+
+/// An image decoded from a file, ready to be drawn any number of times without decoding it again.
+/// Its alpha channel is respected when drawing, and a color key can make one color transparent
+/// for formats without one.
+#[derive(Clone, Debug)]
+pub struct Image {
+    pixels: image::RgbaImage,
+    color_key: Option<Color>,
+}
+
+impl Image {
+    /// Decodes the image at `path`, in any format the `image` crate supports (PNG, JPEG, ...).
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Image, LoadImageError> {
+        let pixels = image::open(path)?.to_rgba8();
+        Ok(Image {
+            pixels,
+            color_key: None,
+        })
+    }
+
+    /// Wraps already decoded pixels, without a color key.
+    pub(crate) fn from_pixels(pixels: image::RgbaImage) -> Image {
+        Image {
+            pixels,
+            color_key: None,
+        }
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.pixels.width()
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.pixels.height()
+    }
+
+    /// Makes the pixels of the given color fully transparent, e.g. magenta for sprite sheets
+    /// without an alpha channel, or stops doing so with `None`.
+    pub fn set_color_key(&mut self, key: Option<Color>) {
+        self.color_key = key;
+    }
+
+    /// Copies the `width`×`height` part whose top-left corner is at (`x`, `y`) into a new image
+    /// with the same color key.
+    pub(crate) fn crop(&self, (x, y, width, height): (u32, u32, u32, u32)) -> Image {
+        Image {
+            pixels: image::imageops::crop_imm(&self.pixels, x, y, width, height).to_image(),
+            color_key: self.color_key,
+        }
+    }
+
+    /// Returns the raw RGBA32 bytes of the image, row by row, with keyed pixels transparent.
+    pub(crate) fn to_rgba_bytes(&self) -> Vec<u8> {
+        let pixels = self.pixels.pixels();
+        pixels
+            .flat_map(|pixel| {
+                let [r, g, b, a] = pixel.0;
+                let keyed = self.color_key == Some(Color { r, g, b });
+                [r, g, b, if keyed { 0 } else { a }]
+            })
+            .collect()
+    }
+
+    /// Returns the color of the pixel at (`x`, `y`) and how opaque it is in the range [0, 1].
+    pub(crate) fn sample(&self, x: u32, y: u32) -> (Color, f32) {
+        let [r, g, b, a] = self.pixels.get_pixel(x, y).0;
+        let color = Color { r, g, b };
+        if self.color_key == Some(color) {
+            return (color, 0.0);
+        }
+        (color, a as f32 / 255.0)
+    }
+
+    /// Returns the color and opacity at the fractional position (`x`, `y`) in pixels within the
+    /// `width`×`height` region whose top-left corner is at (`region_x`, `region_y`), pixel
+    /// centers lying on integer coordinates. Positions past the edges of the region take its
+    /// edge pixels, so neighboring regions don't bleed in. Colors are mixed in the space `mode`
+    /// averages them in.
+    fn sample_linear(
+        &self,
+        mode: Downsampling,
+        (region_x, region_y, width, height): (u32, u32, u32, u32),
+        x: f32,
+        y: f32,
+    ) -> (Color, f32) {
+        let (max_x, max_y) = (width - 1, height - 1);
+        let (x, y) = (x.clamp(0.0, max_x as f32), y.clamp(0.0, max_y as f32));
+        let (left, top) = (x.floor() as u32, y.floor() as u32);
+        let (fx, fy) = (x - left as f32, y - top as f32);
+        let (right, bottom) = ((left + 1).min(max_x), (top + 1).min(max_y));
+        let corners = [
+            (left, top, (1.0 - fx) * (1.0 - fy)),
+            (right, top, fx * (1.0 - fy)),
+            (left, bottom, (1.0 - fx) * fy),
+            (right, bottom, fx * fy),
+        ];
+
+        // Colors are weighted by their opacity, so that transparent pixels don't bleed into the
+        // visible ones
+        let mut sum = [0.0; 3];
+        let mut alpha = 0.0;
+        for (x, y, weight) in corners {
+            let (color, opacity) = self.sample(region_x + x, region_y + y);
+            let weight = weight * opacity;
+            sum[0] += mode.decode(color.r) * weight;
+            sum[1] += mode.decode(color.g) * weight;
+            sum[2] += mode.decode(color.b) * weight;
+            alpha += weight;
+        }
+        if alpha <= 0.0 {
+            return (Color::default(), 0.0);
+        }
+        let channel = |sum: f32| mode.encode(sum / alpha);
+        (
+            Color::new(channel(sum[0]), channel(sum[1]), channel(sum[2])),
+            alpha,
+        )
+    }
+}
+
+impl ScreenContextManager {
+    /// Loads the image at `path` and draws it with its top-left corner at (`x`, `y`). To draw the
+    /// same image repeatedly, load it once with [`Image::load`] and use
+    /// [`blit_image`](ScreenContextManager::blit_image) instead.
+    pub fn draw_image<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        x: i32,
+        y: i32,
+    ) -> Result<(), LoadImageError> {
+        let image = Image::load(path)?;
+        self.blit_image(&image, x, y);
+        Ok(())
+    }
+
+    /// Draws `image` into the framebuffer with its top-left corner at (`x`, `y`), clipping the
+    /// parts that fall outside. Transparent pixels are skipped and translucent ones are blended
+    /// with what is already there.
+    pub fn blit_image(&mut self, image: &Image, x: i32, y: i32) {
+        let region = (0, 0, image.get_width(), image.get_height());
+        self.report(|| DrawCall::Image {
+            x,
+            y,
+            width: region.2,
+            height: region.3,
+        });
+        self.blit_image_region(image, region, x, y);
+    }
+
+    /// Like [`blit_image`](ScreenContextManager::blit_image), but only draws the `width`×`height`
+    /// part of `image` whose top-left corner is at (`source_x`, `source_y`), which must lie
+    /// inside the image.
+    pub(crate) fn blit_image_region(
+        &mut self,
+        image: &Image,
+        (source_x, source_y, width, height): (u32, u32, u32, u32),
+        x: i32,
+        y: i32,
+    ) {
+        if !self.camera.is_identity() {
+            let target = self.camera.pixel_rect(x as i64, y as i64, width, height);
+            let region = (source_x, source_y, width, height);
+            return self.blit_region_scaled(image, region, target, ScaleFilter::Nearest);
+        }
+        let clip = self.clip_bounds();
+        for row in 0..height {
+            let py = y as i64 + row as i64;
+            if !clip.contains_row(py) {
+                continue;
+            }
+            for column in 0..width {
+                let px = x as i64 + column as i64;
+                if clip.contains(px, py) {
+                    let (color, alpha) = image.sample(source_x + column, source_y + row);
+                    let i = (py * self.width as i64 + px) as usize;
+                    self.blend_image_pixel(i, color, alpha);
+                }
+            }
+        }
+    }
+
+    /// Mixes `color` into the framebuffer pixel at index `i` by `alpha`, skipping the blend for
+    /// fully transparent or opaque colors.
+    pub(crate) fn blend_image_pixel(&mut self, i: usize, color: Color, alpha: f32) {
+        if alpha >= 1.0 {
+            self.framebuffer[i] = color;
+        } else if alpha > 0.0 {
+            self.framebuffer[i] = self.color_space.blend(self.framebuffer[i], color, alpha);
+        }
+    }
+
+    /// Draws `image` stretched to cover the `width` × `height` rectangle with its top-left corner
+    /// at (`x`, `y`), sampled with the given filter. Blending and clipping work like
+    /// [`blit_image`](ScreenContextManager::blit_image).
+    pub fn blit_image_scaled(
+        &mut self,
+        image: &Image,
+        (x, y): (i32, i32),
+        (width, height): (u32, u32),
+        filter: ScaleFilter,
+    ) {
+        self.report(|| DrawCall::Image {
+            x,
+            y,
+            width,
+            height,
+        });
+        let region = (0, 0, image.get_width(), image.get_height());
+        let target = self.camera.pixel_rect(x as i64, y as i64, width, height);
+        self.blit_region_scaled(image, region, target, filter);
+    }
+
+    /// Draws the `source_width`×`source_height` part of `image` whose top-left corner is at
+    /// (`source_x`, `source_y`) stretched over the framebuffer rectangle `(x, y, width, height)`,
+    /// bypassing the camera.
+    fn blit_region_scaled(
+        &mut self,
+        image: &Image,
+        (source_x, source_y, source_width, source_height): (u32, u32, u32, u32),
+        (x, y, width, height): (i64, i64, u32, u32),
+        filter: ScaleFilter,
+    ) {
+        if width == 0 || height == 0 || source_width == 0 || source_height == 0 {
+            return;
+        }
+        let region = (source_x, source_y, source_width, source_height);
+        let scale_x = source_width as f32 / width as f32;
+        let scale_y = source_height as f32 / height as f32;
+        let clip = self.clip_bounds();
+
+        for row in 0..height {
+            let py = y + row as i64;
+            if !clip.contains_row(py) {
+                continue;
+            }
+            let sample_y = (row as f32 + 0.5) * scale_y;
+            for column in 0..width {
+                let px = x + column as i64;
+                if !clip.contains(px, py) {
+                    continue;
+                }
+                let sample_x = (column as f32 + 0.5) * scale_x;
+                let (color, alpha) = match filter {
+                    ScaleFilter::Nearest => image.sample(
+                        source_x + (sample_x as u32).min(source_width - 1),
+                        source_y + (sample_y as u32).min(source_height - 1),
+                    ),
+                    ScaleFilter::Linear => {
+                        let mode = self.downsampling;
+                        image.sample_linear(mode, region, sample_x - 0.5, sample_y - 0.5)
+                    }
+                };
+                let i = (py * self.width as i64 + px) as usize;
+                self.blend_image_pixel(i, color, alpha);
+            }
+        }
+    }
+}
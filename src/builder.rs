@@ -0,0 +1,86 @@
+//! This module provides a builder to create a screen with any combination of window options.
+
+use crate::{FullscreenMode, InitError, ScreenContextManager, SdlContext, WindowOptions};
+
+/// Builds a [`ScreenContextManager`], see [`builder`](ScreenContextManager::builder).
+#[derive(Clone, Debug)]
+pub struct ScreenBuilder {
+    title: String,
+    width: u32,
+    height: u32,
+    options: WindowOptions,
+}
+
+impl ScreenBuilder {
+    /// Sets the size of the framebuffer, and of the window it starts out with. 640×480 if not
+    /// set.
+    pub fn size(mut self, width: u32, height: u32) -> ScreenBuilder {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Lets the user resize the window.
+    pub fn resizable(mut self) -> ScreenBuilder {
+        self.options.resizable = true;
+        self
+    }
+
+    /// Synchronizes presentation with the display's refresh rate.
+    pub fn vsync(mut self) -> ScreenBuilder {
+        self.options.vsync = true;
+        self
+    }
+
+    /// Starts out in borderless fullscreen on the desktop, see [`FullscreenMode::Desktop`].
+    pub fn fullscreen(self) -> ScreenBuilder {
+        self.fullscreen_mode(FullscreenMode::Desktop)
+    }
+
+    /// Starts out in the given fullscreen mode.
+    pub fn fullscreen_mode(mut self, mode: FullscreenMode) -> ScreenBuilder {
+        self.options.fullscreen = mode;
+        self
+    }
+
+    /// Creates the window without decorations.
+    pub fn borderless(mut self) -> ScreenBuilder {
+        self.options.borderless = true;
+        self
+    }
+
+    /// Keeps the window above all other windows.
+    pub fn always_on_top(mut self) -> ScreenBuilder {
+        self.options.always_on_top = true;
+        self
+    }
+
+    /// Creates the window hidden.
+    pub fn hidden(mut self) -> ScreenBuilder {
+        self.options.hidden = true;
+        self
+    }
+
+    /// Initializes SDL and creates the window.
+    pub fn build(self) -> Result<ScreenContextManager, InitError> {
+        self.build_in(&SdlContext::new()?)
+    }
+
+    /// Creates the window from an already initialized context, alongside its other windows.
+    pub fn build_in(self, context: &SdlContext) -> Result<ScreenContextManager, InitError> {
+        context.create_window(&self.title, self.width, self.height, self.options)
+    }
+}
+
+impl ScreenContextManager {
+    /// Starts building a screen whose window has the given title, e.g.
+    /// `ScreenContextManager::builder("Demo").size(320, 240).vsync().build()`.
+    pub fn builder(title: &str) -> ScreenBuilder {
+        ScreenBuilder {
+            title: title.to_string(),
+            width: 640,
+            height: 480,
+            options: WindowOptions::default(),
+        }
+    }
+}
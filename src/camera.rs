@@ -0,0 +1,194 @@
+//! This module implements the transforms from drawing coordinates to framebuffer coordinates:
+//! the camera that pans and zooms everything drawn into the scene, and the origin.
+
+use crate::{primitives::Point, ScreenContextManager};
+
+/// Smallest zoom of the camera, keeping the mapping back to world coordinates finite.
+const MIN_ZOOM: f32 = 1e-6;
+
+/// Maps world coordinates to framebuffer coordinates: `screen = (world - offset) * zoom`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Camera {
+    pub offset: Point,
+    pub zoom: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            offset: (0.0, 0.0),
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Camera {
+    /// Creates a camera, raising `zoom` to [`MIN_ZOOM`] if it is smaller or NaN.
+    pub fn new(offset: Point, zoom: f32) -> Camera {
+        Camera {
+            offset,
+            zoom: zoom.max(MIN_ZOOM),
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Camera::default()
+    }
+
+    pub fn world_to_screen(&self, (x, y): Point) -> Point {
+        (
+            (x - self.offset.0) * self.zoom,
+            (y - self.offset.1) * self.zoom,
+        )
+    }
+
+    pub fn screen_to_world(&self, (x, y): Point) -> Point {
+        (x / self.zoom + self.offset.0, y / self.zoom + self.offset.1)
+    }
+
+    /// Maps the `width`×`height` block of whole pixels whose top-left pixel is (`x`, `y`) to the
+    /// framebuffer pixels whose centers it covers, as `(x, y, width, height)`.
+    pub fn pixel_rect(&self, x: i64, y: i64, width: u32, height: u32) -> (i64, i64, u32, u32) {
+        if self.is_identity() {
+            return (x, y, width, height);
+        }
+        // Pixel centers lie on integer coordinates, so a pixel's area starts half a pixel before
+        let corner = |x: i64, y: i64| self.world_to_screen((x as f32 - 0.5, y as f32 - 0.5));
+        let (left, top) = corner(x, y);
+        let (right, bottom) = corner(x + width as i64, y + height as i64);
+        let covered = |from: f32, to: f32| {
+            let (from, to) = (from.min(to).ceil(), from.max(to).ceil());
+            (from as i64, (to - from).max(0.0) as u32)
+        };
+        let ((x, width), (y, height)) = (covered(left, right), covered(top, bottom));
+        (x, y, width, height)
+    }
+}
+
+/// Where drawing coordinates start out from.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Origin {
+    /// The top-left corner, with y growing downwards like rows of the framebuffer.
+    #[default]
+    TopLeft,
+    /// The bottom-left corner, with y growing upwards like in graphs.
+    BottomLeft,
+}
+
+impl ScreenContextManager {
+    /// Moves the origin of drawing coordinates. With [`Origin::BottomLeft`], y = 0 is the bottom
+    /// row and y grows upwards for pixels, batches and the shapes drawn with fractional
+    /// coordinates; the camera's offset is then the world point at the bottom-left corner.
+    /// Images, sprites, tile maps and text still grow downwards from their top-left corner, which
+    /// goes through the camera but isn't mirrored, and clip rectangles stay in framebuffer
+    /// coordinates.
+    pub fn set_origin(&mut self, origin: Origin) {
+        self.origin = origin;
+    }
+
+    /// Returns the origin set with [`set_origin`](ScreenContextManager::set_origin).
+    pub fn get_origin(&self) -> Origin {
+        self.origin
+    }
+
+    /// Returns whether drawing coordinates are framebuffer coordinates already.
+    pub(crate) fn is_untransformed(&self) -> bool {
+        self.camera.is_identity() && self.origin == Origin::TopLeft
+    }
+
+    /// Mirrors a framebuffer row when the origin is at the bottom-left.
+    pub(crate) fn flip_row(&self, y: f32) -> f32 {
+        match self.origin {
+            Origin::TopLeft => y,
+            Origin::BottomLeft => self.height as f32 - 1.0 - y,
+        }
+    }
+
+    /// Pans and zooms all drawing into the scene. The world point (`offset_x`, `offset_y`) ends
+    /// up at the top-left corner of the framebuffer, and one world unit spans `zoom` pixels, so
+    /// stroke widths and radii grow with the zoom too. Drawing addressed in whole pixels, like
+    /// [`plot_pixel`](ScreenContextManager::plot_pixel), batches, images, sprites, tile maps,
+    /// surfaces and text, goes through the camera as well, every pixel becoming the block of
+    /// framebuffer pixels it covers; images are scaled with nearest-neighbor sampling unless
+    /// drawn with [`blit_image_scaled`](ScreenContextManager::blit_image_scaled). The debug
+    /// console and fps counter are composited at present time and aren't affected; to draw other
+    /// overlays, [reset the camera](ScreenContextManager::reset_camera) first. A `zoom` that
+    /// isn't positive, or is NaN, is raised to the smallest zoom supported, one millionth.
+    pub fn set_camera(&mut self, offset_x: f32, offset_y: f32, zoom: f32) {
+        self.camera = Camera::new((offset_x, offset_y), zoom);
+    }
+
+    /// Goes back to drawing in framebuffer coordinates.
+    pub fn reset_camera(&mut self) {
+        self.camera = Camera::default();
+    }
+
+    /// Returns the offset and zoom set with [`set_camera`](ScreenContextManager::set_camera) as
+    /// `(offset_x, offset_y, zoom)`.
+    pub fn get_camera(&self) -> (f32, f32, f32) {
+        (self.camera.offset.0, self.camera.offset.1, self.camera.zoom)
+    }
+
+    /// Returns where the world point (`x`, `y`) lands on the framebuffer, in framebuffer
+    /// coordinates whatever the [origin](ScreenContextManager::set_origin).
+    pub fn world_to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        let (x, y) = self.camera.world_to_screen((x, y));
+        (x, self.flip_row(y))
+    }
+
+    /// Returns the world point that lands on (`x`, `y`) of the framebuffer, e.g. to find what
+    /// the mouse is pointing at.
+    pub fn screen_to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        self.camera.screen_to_world((x, self.flip_row(y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_map_both_ways() {
+        let camera = Camera::new((10.0, -4.0), 2.0);
+        assert_eq!(camera.world_to_screen((12.0, 0.0)), (4.0, 8.0));
+        assert_eq!(camera.screen_to_world((4.0, 8.0)), (12.0, 0.0));
+    }
+
+    #[test]
+    fn zoom_stays_positive() {
+        for zoom in [0.0, -2.0, f32::NAN] {
+            assert_eq!(Camera::new((0.0, 0.0), zoom).zoom, MIN_ZOOM);
+        }
+        let far = Camera::new((0.0, 0.0), 0.0).screen_to_world((1.0, 1.0));
+        assert!(far.0.is_finite() && far.1.is_finite());
+    }
+
+    #[test]
+    fn identity_leaves_pixels_alone() {
+        let camera = Camera::default();
+        assert!(camera.is_identity());
+        assert_eq!(camera.pixel_rect(-3, 7, 5, 2), (-3, 7, 5, 2));
+    }
+
+    #[test]
+    fn zoomed_pixels_cover_blocks() {
+        let camera = Camera::new((0.0, 0.0), 2.0);
+        assert_eq!(camera.pixel_rect(0, 0, 1, 1), (-1, -1, 2, 2));
+        assert_eq!(camera.pixel_rect(3, 1, 2, 1), (5, 1, 4, 2));
+    }
+
+    #[test]
+    fn panned_pixels_move_with_the_offset() {
+        let camera = Camera::new((2.0, 3.0), 1.0);
+        assert_eq!(camera.pixel_rect(2, 3, 4, 4), (0, 0, 4, 4));
+    }
+
+    #[test]
+    fn shrunk_pixels_can_vanish() {
+        let camera = Camera::new((0.0, 0.0), 0.25);
+        let (_, _, width, height) = camera.pixel_rect(1, 1, 1, 1);
+        assert_eq!((width, height), (0, 0));
+        let (_, _, width, height) = camera.pixel_rect(0, 0, 8, 8);
+        assert_eq!((width, height), (2, 2));
+    }
+}
@@ -0,0 +1,52 @@
+//! This module lets a multi-pass renderer snapshot the framebuffer at named points of a frame,
+//! so the intermediate stages can be inspected once the frame has been presented.
+
+use crate::{SaveImageError, ScreenContextManager};
+use std::path::Path;
+
+/// A snapshot of the framebuffer taken with [`capture`](ScreenContextManager::capture).
+#[derive(Clone, Debug)]
+pub struct Capture {
+    pub label: String,
+    pub width: u32,
+    pub height: u32,
+    /// Raw RGB24 pixels, row by row.
+    pub pixels: Vec<u8>,
+}
+
+impl Capture {
+    /// Saves the snapshot as an image whose format is derived from the file extension.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveImageError> {
+        Ok(image::save_buffer(
+            path,
+            &self.pixels,
+            self.width,
+            self.height,
+            image::ColorType::Rgb8,
+        )?)
+    }
+}
+
+impl ScreenContextManager {
+    /// Snapshots the framebuffer as it is right now under the given label. The snapshots taken
+    /// during a frame become available through
+    /// [`get_captures`](ScreenContextManager::get_captures) once that frame is presented.
+    pub fn capture(&mut self, label: &str) {
+        self.pending_captures.push(Capture {
+            label: label.to_string(),
+            width: self.width,
+            height: self.height,
+            pixels: bytemuck::cast_slice(&self.framebuffer).to_vec(),
+        });
+    }
+
+    /// Returns the snapshots taken during the last presented frame, in the order they were taken.
+    pub fn get_captures(&self) -> &[Capture] {
+        &self.captures
+    }
+
+    /// Returns the snapshot with the given label from the last presented frame.
+    pub fn get_capture(&self, label: &str) -> Option<&Capture> {
+        self.captures.iter().find(|capture| capture.label == label)
+    }
+}
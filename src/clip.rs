@@ -0,0 +1,106 @@
+//! This module restricts drawing to a rectangle of the framebuffer, e.g. to keep scrolled content
+//! inside its panel.
+
+use crate::ScreenContextManager;
+
+/// The area drawing may touch, `left` and `top` inclusive, `right` and `bottom` exclusive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ClipRect {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+impl ClipRect {
+    /// The whole of a `width`×`height` buffer.
+    pub fn full(width: u32, height: u32) -> ClipRect {
+        ClipRect {
+            left: 0,
+            top: 0,
+            right: width,
+            bottom: height,
+        }
+    }
+
+    /// The part of the `(x, y, width, height)` rectangle `rect` inside a `width`×`height`
+    /// buffer, or the whole buffer without one.
+    pub fn within(rect: Option<(u32, u32, u32, u32)>, width: u32, height: u32) -> ClipRect {
+        let full = ClipRect::full(width, height);
+        match rect {
+            None => full,
+            Some((x, y, width, height)) => ClipRect {
+                left: x.min(full.right),
+                top: y.min(full.bottom),
+                right: x.saturating_add(width).min(full.right),
+                bottom: y.saturating_add(height).min(full.bottom),
+            },
+        }
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        x >= self.left as i64
+            && x < self.right as i64
+            && y >= self.top as i64
+            && y < self.bottom as i64
+    }
+
+    pub fn contains_row(&self, y: i64) -> bool {
+        y >= self.top as i64 && y < self.bottom as i64
+    }
+}
+
+impl ScreenContextManager {
+    /// Restricts all following drawing to the `width`×`height` rectangle whose top-left corner
+    /// is at (`x`, `y`), given as `(x, y, width, height)`, or lifts the restriction with `None`.
+    /// Clearing the framebuffer and [`render_scanlines`](ScreenContextManager::render_scanlines)
+    /// still cover all of it.
+    pub fn set_clip_rect(&mut self, rect: Option<(u32, u32, u32, u32)>) {
+        self.clip_rect = rect;
+    }
+
+    /// Returns the rectangle set with [`set_clip_rect`](ScreenContextManager::set_clip_rect).
+    pub fn get_clip_rect(&self) -> Option<(u32, u32, u32, u32)> {
+        self.clip_rect
+    }
+
+    /// Returns the area drawing may touch: the clip rectangle within the framebuffer.
+    pub(crate) fn clip_bounds(&self) -> ClipRect {
+        ClipRect::within(self.clip_rect, self.width, self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_rect_covers_the_buffer() {
+        assert_eq!(ClipRect::within(None, 8, 6), ClipRect::full(8, 6));
+    }
+
+    #[test]
+    fn rects_are_cut_to_the_buffer() {
+        let clip = ClipRect::within(Some((6, 2, 10, u32::MAX)), 8, 6);
+        assert_eq!(
+            clip,
+            ClipRect {
+                left: 6,
+                top: 2,
+                right: 8,
+                bottom: 6,
+            }
+        );
+        let outside = ClipRect::within(Some((20, 20, 5, 5)), 8, 6);
+        assert!(!outside.contains(7, 5) && !outside.contains_row(5));
+    }
+
+    #[test]
+    fn right_and_bottom_are_exclusive() {
+        let clip = ClipRect::within(Some((1, 1, 2, 2)), 8, 6);
+        assert!(clip.contains(1, 1) && clip.contains(2, 2));
+        assert!(!clip.contains(3, 2) && !clip.contains(2, 3));
+        assert!(!clip.contains(-1, 1) && !clip.contains(i64::MAX, 1));
+        assert!(clip.contains_row(2) && !clip.contains_row(0));
+    }
+}
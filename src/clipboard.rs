@@ -0,0 +1,24 @@
+//! This module gives access to the system clipboard, for copying text out of the program and
+//! pasting it in.
+
+use crate::{ScreenContextManager, WindowError};
+use std::ffi::CString;
+
+impl ScreenContextManager {
+    /// Returns the text on the clipboard, empty if there is none.
+    pub fn get_clipboard_text(&self) -> Result<String, WindowError> {
+        let clipboard = self.output.canvas.window().subsystem().clipboard();
+        if !clipboard.has_clipboard_text() {
+            return Ok(String::new());
+        }
+        Ok(clipboard.clipboard_text()?)
+    }
+
+    /// Puts `text` on the clipboard, replacing what was there.
+    pub fn set_clipboard_text(&mut self, text: &str) -> Result<(), WindowError> {
+        // SDL's wrapper panics on NUL characters, so they are reported as an error beforehand
+        CString::new(text)?;
+        let clipboard = self.output.canvas.window().subsystem().clipboard();
+        Ok(clipboard.set_clipboard_text(text)?)
+    }
+}
@@ -0,0 +1,145 @@
+//! This module converts between the sRGB encoding of the framebuffer and linear light, where
+//! averaging and blending colors is physically correct.
+
+use crate::{Color, ScreenContextManager};
+use std::sync::OnceLock;
+
+/// Converts an sRGB encoded channel to linear light in the range [0, 1].
+pub fn to_linear(value: u8) -> f32 {
+    static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let mut table = [0.0; 256];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let v = i as f32 / 255.0;
+            *entry = if v <= 0.04045 {
+                v / 12.92
+            } else {
+                ((v + 0.055) / 1.055).powf(2.4)
+            };
+        }
+        table
+    });
+    table[value as usize]
+}
+
+/// Converts linear light in the range [0, 1] to an sRGB encoded channel.
+pub fn from_linear(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let encoded = if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
+
+/// The space the color components passed to drawing calls are given in, which is also the space
+/// partially covered pixels are blended in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Components are sRGB encoded, as in most image files and color pickers, and get blended
+    /// as they are.
+    #[default]
+    Srgb,
+    /// Components are linear light, as computed by lighting or physically based code, and
+    /// blending happens in linear light too.
+    Linear,
+}
+
+impl ColorSpace {
+    /// Converts a component in the range [0, 1] given in this space to an sRGB encoded channel.
+    pub(crate) fn encode(self, value: f32) -> u8 {
+        match self {
+            ColorSpace::Srgb => (value * 255.0).round() as u8,
+            ColorSpace::Linear => from_linear(value),
+        }
+    }
+
+    /// Converts components given in this space to a color of the framebuffer.
+    pub(crate) fn color(self, r: f32, g: f32, b: f32) -> Color {
+        Color {
+            r: self.encode(r),
+            g: self.encode(g),
+            b: self.encode(b),
+        }
+    }
+
+    /// Mixes `over` onto `under` by `alpha` in the range [0, 1], in this space.
+    pub(crate) fn blend(self, under: Color, over: Color, alpha: f32) -> Color {
+        match self {
+            ColorSpace::Srgb => under.blend(over, alpha),
+            ColorSpace::Linear => {
+                let mix = |from: u8, to: u8| {
+                    let (from, to) = (to_linear(from), to_linear(to));
+                    from_linear(from + (to - from) * alpha)
+                };
+                Color {
+                    r: mix(under.r, over.r),
+                    g: mix(under.g, over.g),
+                    b: mix(under.b, over.b),
+                }
+            }
+        }
+    }
+}
+
+impl ScreenContextManager {
+    /// Declares the space of the color components given from now on to
+    /// [`set_color`](ScreenContextManager::set_color) and the other calls taking real numbers
+    /// for colors, and the space anti-aliased edges, images and text are blended in. The
+    /// framebuffer itself always holds sRGB, which is what displays and image files expect.
+    /// Shapes drawn with [`RasterMode::FixedPoint`](crate::RasterMode::FixedPoint) keep
+    /// blending sRGB values, so that they stay bit-identical everywhere.
+    pub fn set_color_space(&mut self, space: ColorSpace) {
+        self.color_space = space;
+    }
+
+    /// Returns the space set with [`set_color_space`](ScreenContextManager::set_color_space).
+    pub fn get_color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_light_round_trips() {
+        assert_eq!(to_linear(0), 0.0);
+        assert_eq!(to_linear(255), 1.0);
+        for value in 0..=255 {
+            assert_eq!(from_linear(to_linear(value)), value);
+        }
+        assert_eq!(from_linear(-1.0), 0);
+        assert_eq!(from_linear(2.0), 255);
+    }
+
+    #[test]
+    fn linear_light_is_darker_in_srgb() {
+        assert_eq!(from_linear(0.5), 188);
+        assert!(to_linear(128) < 0.25);
+    }
+
+    #[test]
+    fn components_are_encoded_per_space() {
+        assert_eq!(
+            ColorSpace::Srgb.color(0.0, 0.5, 1.0),
+            Color::new(0, 128, 255)
+        );
+        assert_eq!(
+            ColorSpace::Linear.color(0.0, 0.5, 1.0),
+            Color::new(0, 188, 255)
+        );
+    }
+
+    #[test]
+    fn linear_blending_mixes_light() {
+        let black = Color::new(0, 0, 0);
+        let white = Color::new(255, 255, 255);
+        let mixed = ColorSpace::Linear.blend(black, white, 0.5);
+        assert_eq!(mixed, Color::new(188, 188, 188));
+        assert_eq!(ColorSpace::Linear.blend(black, white, 0.0), black);
+        assert_eq!(ColorSpace::Linear.blend(black, white, 1.0), white);
+    }
+}
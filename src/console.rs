@@ -0,0 +1,117 @@
+//! This module implements the debug console, a log of text lines drawn over the presented frame
+//! that can be shown and hidden with a key.
+
+use crate::clip::ClipRect;
+use crate::text::{self, GLYPH_SIZE};
+use crate::{Color, Key, ScreenContextManager};
+use sdl2::event::Event;
+use std::collections::VecDeque;
+
+/// Lines kept by default.
+const DEFAULT_LINES: usize = 16;
+
+/// The debug console's log and state.
+pub(crate) struct Console {
+    lines: VecDeque<String>,
+    capacity: usize,
+    visible: bool,
+    toggle_key: Option<Key>,
+}
+
+impl Default for Console {
+    fn default() -> Console {
+        Console {
+            lines: VecDeque::new(),
+            capacity: DEFAULT_LINES,
+            visible: false,
+            toggle_key: None,
+        }
+    }
+}
+
+impl Console {
+    /// Shows or hides the console if `event` is a press of the toggle key, returning whether it
+    /// was handled.
+    pub fn handle(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::KeyDown {
+                keycode: Some(key),
+                repeat,
+                ..
+            } if Some(key) == self.toggle_key.map(Key::to_sdl) => {
+                if !repeat {
+                    self.visible = !self.visible;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Draws the kept lines over a dark backdrop along the top of `pixels`.
+    pub fn draw(&self, pixels: &mut [Color], width: u32, height: u32) {
+        if !self.visible {
+            return;
+        }
+        const MARGIN: u32 = 4;
+
+        let backdrop_height = (self.capacity as u32 * GLYPH_SIZE + 2 * MARGIN).min(height);
+        for pixel in &mut pixels[..(backdrop_height * width) as usize] {
+            *pixel = pixel.blend(Color::default(), 0.75);
+        }
+        for (i, line) in self.lines.iter().enumerate() {
+            let y = MARGIN + i as u32 * GLYPH_SIZE;
+            let white = Color::new(255, 255, 255);
+            text::draw_text(
+                pixels,
+                width,
+                ClipRect::full(width, height),
+                (MARGIN as i32, y as i32),
+                line,
+                white,
+            );
+        }
+    }
+}
+
+impl ScreenContextManager {
+    /// Adds text to the debug console, one line per line of `text`. Only the most recent lines
+    /// are kept, see [`set_console_lines`](ScreenContextManager::set_console_lines).
+    pub fn console_log(&mut self, text: &str) {
+        let console = &mut self.console;
+        console.lines.extend(text.lines().map(str::to_owned));
+        while console.lines.len() > console.capacity {
+            console.lines.pop_front();
+        }
+    }
+
+    /// Sets how many lines the debug console keeps and shows, 16 by default.
+    pub fn set_console_lines(&mut self, lines: usize) {
+        self.console.capacity = lines;
+        while self.console.lines.len() > lines {
+            self.console.lines.pop_front();
+        }
+    }
+
+    /// Shows or hides the debug console. It is drawn along the top of the presented frame, the
+    /// framebuffer itself is left untouched.
+    pub fn show_console(&mut self, show: bool) {
+        self.console.visible = show;
+    }
+
+    /// Returns whether the debug console is being shown.
+    pub fn is_console_visible(&self) -> bool {
+        self.console.visible
+    }
+
+    /// Makes the given key show and hide the debug console while events are being read, e.g.
+    /// [`Key::Backquote`], or disables the shortcut with `None`. The key presses handling the
+    /// shortcut are not delivered as events.
+    pub fn set_console_key(&mut self, key: Option<Key>) {
+        self.console.toggle_key = key;
+    }
+}
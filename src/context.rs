@@ -0,0 +1,193 @@
+//! This module owns the SDL library itself, which can only be initialized once, so that any
+//! number of windows can share it.
+
+use crate::{InitError, ScreenContextManager, WindowOptions};
+use sdl2::{
+    event::Event, keyboard::Scancode, mouse::MouseState, mouse::RelativeMouseState, video::Window,
+    EventPump, Sdl, VideoSubsystem,
+};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::ffi::c_void;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// The initialized SDL library, from which several windows can be created. Every window gets its
+/// own framebuffer and only receives the events meant for it; events that don't belong to any
+/// window, like [`ScreenEvent::Quit`](crate::ScreenEvent::Quit), are delivered to all of them.
+pub struct SdlContext {
+    // Keeps SDL initialized for as long as the context is around
+    _sdl: Sdl,
+    video: VideoSubsystem,
+    events: Rc<RefCell<EventRouter>>,
+}
+
+impl SdlContext {
+    /// Initializes SDL. Only one context can exist at a time.
+    pub fn new() -> Result<SdlContext, InitError> {
+        Self::from_sdl(sdl2::init()?)
+    }
+
+    /// Wraps SDL as already initialized by an application embedding this crate. The context reads
+    /// all events, so the application must not have created an event pump of its own.
+    pub fn from_sdl(sdl: Sdl) -> Result<SdlContext, InitError> {
+        let video = sdl.video()?;
+        let events = EventRouter {
+            pump: sdl.event_pump()?,
+            queues: HashMap::new(),
+        };
+        Ok(SdlContext {
+            _sdl: sdl,
+            video,
+            events: Rc::new(RefCell::new(events)),
+        })
+    }
+
+    /// Creates a new window with the given title and options, with a framebuffer of the given
+    /// size.
+    pub fn create_window(
+        &self,
+        title: &str,
+        width: u32,
+        height: u32,
+        options: WindowOptions,
+    ) -> Result<ScreenContextManager, InitError> {
+        ScreenContextManager::create(self, title, width, height, options, false)
+    }
+
+    /// Takes over a window the application created through the same SDL, presenting a
+    /// framebuffer of the given size on it.
+    pub fn adopt_window(
+        &self,
+        window: Window,
+        width: u32,
+        height: u32,
+        vsync: bool,
+    ) -> Result<ScreenContextManager, InitError> {
+        ScreenContextManager::from_window(self, window, width, height, vsync, false)
+    }
+
+    /// Like [`adopt_window`](SdlContext::adopt_window), but for a window created outside of SDL,
+    /// e.g. by a GUI toolkit, given its native handle (an `HWND` on Windows, an X11 `Window` on
+    /// Linux, an `NSWindow*` on macOS).
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid native window that outlives the returned screen.
+    pub unsafe fn adopt_native_window(
+        &self,
+        handle: *const c_void,
+        width: u32,
+        height: u32,
+        vsync: bool,
+    ) -> Result<ScreenContextManager, InitError> {
+        let raw = sdl2::sys::SDL_CreateWindowFrom(handle);
+        if raw.is_null() {
+            return Err(sdl2::get_error().into());
+        }
+        let window = Window::from_ll(self.video.clone(), raw);
+        self.adopt_window(window, width, height, vsync)
+    }
+
+    pub(crate) fn video(&self) -> &VideoSubsystem {
+        &self.video
+    }
+
+    /// Sets up a queue for the window with the given id, returning the router that fills it.
+    pub(crate) fn register(&self, window_id: u32) -> Rc<RefCell<EventRouter>> {
+        self.events
+            .borrow_mut()
+            .queues
+            .insert(window_id, VecDeque::new());
+        Rc::clone(&self.events)
+    }
+}
+
+/// Reads SDL's events and sorts them into one queue per window.
+pub(crate) struct EventRouter {
+    pump: EventPump,
+    queues: HashMap<u32, VecDeque<Event>>,
+}
+
+impl EventRouter {
+    /// Moves every event waiting in SDL into the queues of the windows they belong to.
+    fn poll(&mut self) {
+        while let Some(event) = self.pump.poll_event() {
+            self.route(event);
+        }
+    }
+
+    fn route(&mut self, event: Event) {
+        match event.get_window_id() {
+            // Events for windows that are gone are dropped
+            Some(id) => {
+                if let Some(queue) = self.queues.get_mut(&id) {
+                    queue.push_back(event);
+                }
+            }
+            None => {
+                for queue in self.queues.values_mut() {
+                    queue.push_back(event.clone());
+                }
+            }
+        }
+    }
+
+    /// Moves all the events for the window with the given id into `events`.
+    pub fn take(&mut self, window_id: u32, events: &mut VecDeque<Event>) {
+        self.poll();
+        if let Some(queue) = self.queues.get_mut(&window_id) {
+            events.append(queue);
+        }
+    }
+
+    /// Blocks until there is at least one event for the window with the given id, or until the
+    /// timeout (if any) runs out.
+    pub fn wait(&mut self, window_id: u32, timeout: Option<Duration>) {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        self.poll();
+        while self.queues.get(&window_id).is_some_and(VecDeque::is_empty) {
+            let event = match deadline {
+                None => Some(self.pump.wait_event()),
+                Some(deadline) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if left.is_zero() {
+                        return;
+                    }
+                    // Rounded up, so as not to spin through the last partial millisecond
+                    let millis = left.as_micros().div_ceil(1000);
+                    self.pump
+                        .wait_event_timeout(millis.min(u32::MAX as u128) as u32)
+                }
+            };
+            if let Some(event) = event {
+                self.route(event);
+            }
+        }
+    }
+
+    /// Lets SDL process its events without reading them, so that windows stay responsive.
+    pub fn pump(&mut self) {
+        self.pump.pump_events();
+    }
+
+    /// Returns whether the key at `scancode` is held down according to SDL's keyboard state.
+    pub fn is_scancode_pressed(&self, scancode: Scancode) -> bool {
+        self.pump.keyboard_state().is_scancode_pressed(scancode)
+    }
+
+    /// Returns SDL's mouse state, relative to the window with mouse focus.
+    pub fn mouse_state(&self) -> MouseState {
+        self.pump.mouse_state()
+    }
+
+    /// Returns how far the mouse moved since the last call, according to SDL.
+    pub fn relative_mouse_state(&self) -> RelativeMouseState {
+        self.pump.relative_mouse_state()
+    }
+
+    /// Forgets the queue of a window that is being destroyed.
+    pub fn unregister(&mut self, window_id: u32) {
+        self.queues.remove(&window_id);
+    }
+}
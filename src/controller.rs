@@ -0,0 +1,86 @@
+//! This module provides game controller support, opening controllers as they get connected.
+
+use crate::{ControllerAxis, ControllerButton, InitError, ScreenContextManager, ScreenEvent};
+use sdl2::{controller::GameController, event::Event, GameControllerSubsystem};
+
+/// The game controllers opened so far.
+pub(crate) struct Controllers {
+    subsystem: GameControllerSubsystem,
+    open: Vec<GameController>,
+}
+
+impl Controllers {
+    /// Opens or closes controllers on connection events, returning the event to deliver instead.
+    /// Other events are handed back untouched.
+    pub fn handle(&mut self, event: Event) -> Result<ScreenEvent, Event> {
+        match event {
+            Event::ControllerDeviceAdded { which, .. } => match self.subsystem.open(which) {
+                Ok(controller) => {
+                    let id = controller.instance_id();
+                    self.open.push(controller);
+                    Ok(ScreenEvent::ControllerConnected { id })
+                }
+                // Nothing to announce for a controller that can't be used
+                Err(_) => Err(event),
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.open
+                    .retain(|controller| controller.instance_id() != which);
+                Ok(ScreenEvent::ControllerDisconnected { id: which })
+            }
+            event => Err(event),
+        }
+    }
+
+    fn get(&self, id: u32) -> Option<&GameController> {
+        self.open
+            .iter()
+            .find(|controller| controller.instance_id() == id)
+    }
+}
+
+impl ScreenContextManager {
+    /// Starts looking for game controllers. Every controller already connected, and every one
+    /// connected later, is announced by a [`ScreenEvent::ControllerConnected`] carrying the id to
+    /// read its state with.
+    pub fn enable_controllers(&mut self) -> Result<(), InitError> {
+        if self.controllers.is_none() {
+            let sdl = self.output.canvas.window().subsystem().sdl();
+            self.controllers = Some(Controllers {
+                subsystem: sdl.game_controller()?,
+                open: Vec::new(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the ids of the connected controllers.
+    pub fn get_controllers(&self) -> Vec<u32> {
+        let open = self.controllers.iter().flat_map(|c| c.open.iter());
+        open.map(GameController::instance_id).collect()
+    }
+
+    /// Returns the name of a connected controller, or `None` if there is no such controller.
+    pub fn get_controller_name(&self, id: u32) -> Option<String> {
+        self.controllers.as_ref()?.get(id).map(GameController::name)
+    }
+
+    /// Returns the position of a stick or trigger, in the range [-1, 1] for sticks (negative
+    /// being left or up) and [0, 1] for triggers. Disconnected controllers read 0.
+    pub fn get_controller_axis(&self, id: u32, axis: ControllerAxis) -> f32 {
+        let value = self
+            .controllers
+            .as_ref()
+            .and_then(|controllers| controllers.get(id))
+            .map_or(0, |controller| controller.axis(axis.to_sdl()));
+        (value as f32 / i16::MAX as f32).max(-1.0)
+    }
+
+    /// Returns whether a button of a controller is being held down.
+    pub fn is_controller_button_down(&self, id: u32, button: ControllerButton) -> bool {
+        self.controllers
+            .as_ref()
+            .and_then(|controllers| controllers.get(id))
+            .is_some_and(|controller| controller.button(button.to_sdl()))
+    }
+}
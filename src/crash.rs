@@ -0,0 +1,107 @@
+//! This module dumps the framebuffer and a small state report to disk when the render loop
+//! panics, so that intermittent rendering bugs can be diagnosed after the fact.
+
+use crate::{CrashDumpError, ScreenContextManager};
+use sdl2::event::Event;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+/// Amount of events kept around for the crash report
+pub const RECENT_EVENTS: usize = 16;
+
+/// A closure set with [`set_crash_reporter`](ScreenContextManager::set_crash_reporter).
+pub(crate) type CrashReporter = Box<dyn FnMut(&CrashDumpError)>;
+
+impl ScreenContextManager {
+    /// Runs `f`, and if it panics saves the framebuffer as `crash_frame.png` together with a
+    /// `crash_report.txt` (frame index, panic message and the last few events) into `dir` before
+    /// resuming the panic. Failures to write the dump are handed to the
+    /// [crash reporter](ScreenContextManager::set_crash_reporter), if any, and ignored otherwise.
+    pub fn with_crash_dump<P, F, R>(&mut self, dir: P, f: F) -> R
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&mut ScreenContextManager) -> R,
+    {
+        let was_recording = self.recent_events.is_some();
+        if !was_recording {
+            self.recent_events = Some(Default::default());
+        }
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(self)));
+
+        match result {
+            Ok(value) => {
+                if !was_recording {
+                    self.recent_events = None;
+                }
+                value
+            }
+            Err(payload) => {
+                if let Err(e) = self.dump_crash(dir.as_ref(), payload.as_ref()) {
+                    self.report_crash_error(e.into());
+                }
+                panic::resume_unwind(payload)
+            }
+        }
+    }
+
+    /// Calls `reporter` with every failure to write a crash dump in
+    /// [`with_crash_dump`](ScreenContextManager::with_crash_dump), which can't return them since
+    /// it goes on panicking. Without a reporter they are ignored.
+    pub fn set_crash_reporter<F>(&mut self, reporter: F)
+    where
+        F: FnMut(&CrashDumpError) + 'static,
+    {
+        self.crash_reporter = Some(Box::new(reporter));
+    }
+
+    /// Removes the reporter set with
+    /// [`set_crash_reporter`](ScreenContextManager::set_crash_reporter).
+    pub fn clear_crash_reporter(&mut self) {
+        self.crash_reporter = None;
+    }
+
+    fn report_crash_error(&mut self, error: CrashDumpError) {
+        if let Some(reporter) = self.crash_reporter.as_mut() {
+            reporter(&error);
+        }
+    }
+
+    fn dump_crash(&mut self, dir: &Path, payload: &(dyn Any + Send)) -> std::io::Result<()> {
+        fs::create_dir_all(dir)?;
+
+        // The report is still worth writing without the frame
+        if let Err(e) = self.save_img(dir.join("crash_frame.png")) {
+            self.report_crash_error(e.into());
+        }
+
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("<non-string panic payload>");
+
+        let mut report = format!(
+            "panic: {}\nframe index: {}\nframebuffer: {}x{}\n\nlast events (oldest first):\n",
+            message, self.frame_index, self.width, self.height
+        );
+        for event in self.recent_events.iter().flatten() {
+            report.push_str(&format!("{:?}\n", event));
+        }
+
+        fs::write(dir.join("crash_report.txt"), report)
+    }
+}
+
+/// Remembers `event` for the crash report, if a crash dump is armed.
+pub fn record_event(recent: &mut Option<VecDeque<Event>>, event: &Event) {
+    if let Some(recent) = recent {
+        if recent.len() == RECENT_EVENTS {
+            recent.pop_front();
+        }
+        recent.push_back(event.clone());
+    }
+}
@@ -0,0 +1,167 @@
+//! This module implements the software mouse cursor, drawn into the presented frame instead of by
+//! the operating system, along with control over the operating system's cursor.
+
+use crate::{Color, Image, ScreenContextManager, WindowError};
+use sdl2::{mouse::Cursor, pixels::PixelFormatEnum, surface::Surface};
+
+/// The built-in arrow: `X` is the outline, `.` the fill and spaces are transparent.
+const ARROW: [&str; 16] = [
+    "X         ",
+    "XX        ",
+    "X.X       ",
+    "X..X      ",
+    "X...X     ",
+    "X....X    ",
+    "X.....X   ",
+    "X......X  ",
+    "X.......X ",
+    "X........X",
+    "X.....XXXX",
+    "X..X..X   ",
+    "X.X X..X  ",
+    "XX  X..X  ",
+    "X    X..X ",
+    "      XX  ",
+];
+
+/// An image drawn as the mouse cursor by
+/// [`set_software_cursor`](ScreenContextManager::set_software_cursor).
+#[derive(Clone, Debug)]
+pub struct CursorSprite {
+    width: u32,
+    height: u32,
+    /// Row by row, `None` being transparent.
+    pixels: Vec<Option<Color>>,
+    hotspot: (u32, u32),
+}
+
+impl CursorSprite {
+    /// Creates a sprite from `width`×`height` pixels given row by row, where `None` is
+    /// transparent. The `hotspot` is the pixel of the sprite that points at the mouse position.
+    ///
+    /// # Panics
+    ///
+    /// If the amount of pixels doesn't match the size.
+    pub fn new(
+        width: u32,
+        height: u32,
+        pixels: Vec<Option<Color>>,
+        hotspot: (u32, u32),
+    ) -> CursorSprite {
+        assert_eq!(pixels.len(), (width * height) as usize);
+        CursorSprite {
+            width,
+            height,
+            pixels,
+            hotspot,
+        }
+    }
+
+    /// The built-in arrow pointer, white with a black outline.
+    pub fn arrow() -> CursorSprite {
+        let pixels = ARROW
+            .iter()
+            .flat_map(|row| row.chars())
+            .map(|c| match c {
+                'X' => Some(Color::new(0, 0, 0)),
+                '.' => Some(Color::new(255, 255, 255)),
+                _ => None,
+            })
+            .collect();
+        CursorSprite::new(ARROW[0].len() as u32, ARROW.len() as u32, pixels, (0, 0))
+    }
+
+    /// Draws the sprite into `pixels` with its hotspot at (`x`, `y`), clipped to the buffer.
+    pub(crate) fn draw(&self, pixels: &mut [Color], width: u32, height: u32, x: i32, y: i32) {
+        let left = x - self.hotspot.0 as i32;
+        let top = y - self.hotspot.1 as i32;
+        for row in 0..self.height {
+            for column in 0..self.width {
+                let (px, py) = (left + column as i32, top + row as i32);
+                if px < 0 || py < 0 || px as u32 >= width || py as u32 >= height {
+                    continue;
+                }
+                if let Some(color) = self.pixels[(row * self.width + column) as usize] {
+                    pixels[(py as u32 * width + px as u32) as usize] = color;
+                }
+            }
+        }
+    }
+}
+
+impl ScreenContextManager {
+    /// Hides the operating system's cursor and draws `sprite` at the mouse position over every
+    /// presented frame instead, or goes back to the system cursor with `None`. Being drawn in
+    /// framebuffer pixels, the cursor scales along with the framebuffer.
+    pub fn set_software_cursor(&mut self, sprite: Option<CursorSprite>) {
+        let mouse = self.output.canvas.window().subsystem().sdl().mouse();
+        mouse.show_cursor(sprite.is_none());
+        self.software_cursor = sprite;
+    }
+
+    /// Shows or hides the operating system's cursor while it is over the window, e.g. for
+    /// programs drawing a cursor of their own. While a
+    /// [software cursor](Self::set_software_cursor) is set, the system cursor stays hidden and
+    /// this does nothing; remove the software cursor first to go back to the system one.
+    pub fn show_cursor(&mut self, show: bool) {
+        if self.software_cursor.is_some() {
+            return;
+        }
+        let mouse = self.output.canvas.window().subsystem().sdl().mouse();
+        mouse.show_cursor(show);
+    }
+
+    /// Returns whether a cursor is shown over the window, either the operating system's or the
+    /// [software cursor](Self::set_software_cursor).
+    pub fn is_cursor_visible(&self) -> bool {
+        if self.software_cursor.is_some() {
+            return true;
+        }
+        let mouse = self.output.canvas.window().subsystem().sdl().mouse();
+        mouse.is_cursor_showing()
+    }
+
+    /// Replaces the operating system's cursor with `image`, its pixel at `hotspot` pointing at
+    /// the mouse position. Unlike [`set_software_cursor`](Self::set_software_cursor), the cursor
+    /// keeps its size in screen pixels and moves without waiting for the next frame.
+    pub fn set_cursor_image(
+        &mut self,
+        image: &Image,
+        (hotspot_x, hotspot_y): (u32, u32),
+    ) -> Result<(), WindowError> {
+        let (width, height) = (image.get_width(), image.get_height());
+        let mut bytes = image.to_rgba_bytes();
+        let surface = Surface::from_data(
+            &mut bytes,
+            width,
+            height,
+            width * 4,
+            PixelFormatEnum::RGBA32,
+        )?;
+        let cursor = Cursor::from_surface(surface, hotspot_x as i32, hotspot_y as i32)?;
+        cursor.set();
+        // SDL only refers to the cursor, so it has to be kept alive while in use
+        self.cursor_image = Some(cursor);
+        Ok(())
+    }
+
+    /// Goes back to the operating system's default arrow cursor.
+    pub fn reset_cursor_image(&mut self) {
+        // SDL switches back to the default cursor when the active one is freed
+        self.cursor_image = None;
+    }
+
+    /// Draws the software cursor, if any, into `pixels` while the mouse is over the window.
+    pub(crate) fn draw_software_cursor(&self, pixels: &mut [Color]) {
+        let sprite = match self.software_cursor.as_ref() {
+            Some(sprite) => sprite,
+            None => return,
+        };
+        let mouse = self.output.canvas.window().subsystem().sdl().mouse();
+        if mouse.focused_window_id() != Some(self.window_id) {
+            return;
+        }
+        let (x, y) = self.get_mouse_position();
+        sprite.draw(pixels, self.width, self.height, x, y);
+    }
+}
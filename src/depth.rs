@@ -0,0 +1,54 @@
+//! This module implements the optional depth buffer, which lets software 3D renderers draw
+//! surfaces in any order and still have the nearest one end up on top.
+
+use crate::{DrawCall, ScreenContextManager};
+
+impl ScreenContextManager {
+    /// Adds a depth buffer to the framebuffer, cleared to be infinitely far away, or removes it.
+    pub fn set_depth_buffer(&mut self, enabled: bool) {
+        self.depth_buffer = enabled.then(|| vec![f32::INFINITY; self.framebuffer.len()]);
+    }
+
+    /// Returns whether there is a depth buffer, see
+    /// [`set_depth_buffer`](ScreenContextManager::set_depth_buffer).
+    pub fn has_depth_buffer(&self) -> bool {
+        self.depth_buffer.is_some()
+    }
+
+    /// Resets every pixel of the depth buffer to be infinitely far away, typically along with
+    /// clearing the framebuffer at the start of a frame.
+    pub fn clear_depth(&mut self) {
+        if let Some(depth) = self.depth_buffer.as_mut() {
+            depth.fill(f32::INFINITY);
+        }
+    }
+
+    /// Plots a pixel like [`plot_pixel`](ScreenContextManager::plot_pixel) at depth `z`, but
+    /// only if it is nearer than what was drawn there before, smaller depths being nearer. The
+    /// pixel's depth is then updated to `z`. Without a depth buffer the pixel is always plotted.
+    pub fn plot_pixel_depth(&mut self, x: u32, y: u32, z: f32) {
+        self.report(|| DrawCall::PlotPixelDepth(x, y, z));
+        if self.camera.is_identity() {
+            if let Some((x, y)) = self.locate_pixel(x, y) {
+                self.plot_depth_tested(x, y, z);
+            }
+            return;
+        }
+        for (x, y) in self.camera_pixels(x, y) {
+            self.plot_depth_tested(x, y, z);
+        }
+    }
+
+    /// Sets the framebuffer pixel at (`x`, `y`) if `z` passes the depth test.
+    fn plot_depth_tested(&mut self, x: u32, y: u32, z: f32) {
+        if let Some(depth) = self.depth_buffer.as_mut() {
+            let stored = &mut depth[(y * self.width + x) as usize];
+            // NaN never passes, so it can't punch holes into what is behind
+            if z.is_nan() || z >= *stored {
+                return;
+            }
+            *stored = z;
+        }
+        self.set_pixel(x, y);
+    }
+}
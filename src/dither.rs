@@ -0,0 +1,142 @@
+//! This module implements the dithering pass applied to the presented frame, trading the banding
+//! of smooth gradients reduced to a few colors for fine noise.
+
+use crate::{Color, ScreenContextManager};
+
+/// How [`set_dithering`](ScreenContextManager::set_dithering) spreads the rounding error when
+/// every channel is reduced to `levels` evenly spaced values, from 2 to 256.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Dithering {
+    /// Thresholds from a repeating 4×4 Bayer matrix, giving a regular crosshatch pattern that
+    /// stays put while things move.
+    Ordered { levels: u32 },
+    /// Floyd-Steinberg error diffusion, pushing every pixel's error onto the ones not yet visited,
+    /// which looks more natural but shimmers in animations.
+    ErrorDiffusion { levels: u32 },
+}
+
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Reduces every channel of `pixels`, a `width`×`height` buffer, to the levels of `mode`.
+pub fn dither(pixels: &mut [Color], width: u32, height: u32, mode: Dithering) {
+    let (width, height) = (width as usize, height as usize);
+    let levels = match mode {
+        Dithering::Ordered { levels } | Dithering::ErrorDiffusion { levels } => levels,
+    };
+    let step = 255.0 / (levels.clamp(2, 256) - 1) as f32;
+    let quantize = |value: f32| ((value / step).round() * step).clamp(0.0, 255.0);
+
+    match mode {
+        Dithering::Ordered { .. } => {
+            for (y, row) in pixels.chunks_mut(width).enumerate().take(height) {
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    // Centered on 0, so that flat areas keep their average brightness
+                    let threshold = (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5;
+                    let channel = |value: u8| quantize(value as f32 + threshold * step) as u8;
+                    *pixel = Color {
+                        r: channel(pixel.r),
+                        g: channel(pixel.g),
+                        b: channel(pixel.b),
+                    };
+                }
+            }
+        }
+        Dithering::ErrorDiffusion { .. } => {
+            // The errors carried into the current row and the next one
+            let mut current = vec![[0.0f32; 3]; width + 2];
+            let mut next = vec![[0.0f32; 3]; width + 2];
+            for row in pixels.chunks_mut(width).take(height) {
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    let mut channels = [pixel.r, pixel.g, pixel.b];
+                    for (c, channel) in channels.iter_mut().enumerate() {
+                        let wanted = *channel as f32 + current[x + 1][c];
+                        let got = quantize(wanted);
+                        let error = wanted - got;
+                        current[x + 2][c] += error * 7.0 / 16.0;
+                        next[x][c] += error * 3.0 / 16.0;
+                        next[x + 1][c] += error * 5.0 / 16.0;
+                        next[x + 2][c] += error / 16.0;
+                        *channel = got as u8;
+                    }
+                    let [r, g, b] = channels;
+                    *pixel = Color { r, g, b };
+                }
+                std::mem::swap(&mut current, &mut next);
+                next.fill([0.0; 3]);
+            }
+        }
+    }
+}
+
+impl ScreenContextManager {
+    /// Reduces every presented frame to fewer colors with the given dithering, or stops doing so
+    /// with `None`. Meant for stylized output like retro palettes; the framebuffer itself is left
+    /// untouched, and overlays like the fps counter are drawn after it.
+    pub fn set_dithering(&mut self, dithering: Option<Dithering>) {
+        self.dithering = dithering;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(value: u8, count: usize) -> Vec<Color> {
+        vec![Color::new(value, value, value); count]
+    }
+
+    fn white_count(pixels: &[Color]) -> usize {
+        pixels.iter().filter(|pixel| pixel.r == 255).count()
+    }
+
+    #[test]
+    fn full_levels_leave_pixels_alone() {
+        let pixels: Vec<Color> = (0..=255)
+            .map(|value| Color::new(value, value, 255 - value))
+            .collect();
+        for mode in [
+            Dithering::Ordered { levels: 256 },
+            Dithering::ErrorDiffusion { levels: 256 },
+        ] {
+            let mut dithered = pixels.clone();
+            dither(&mut dithered, 16, 16, mode);
+            assert_eq!(dithered, pixels);
+        }
+    }
+
+    #[test]
+    fn ordered_dithering_keeps_the_average() {
+        let mut pixels = gray(128, 16);
+        dither(&mut pixels, 4, 4, Dithering::Ordered { levels: 2 });
+        assert!(pixels.iter().all(|pixel| pixel.r == 0 || pixel.r == 255));
+        assert_eq!(white_count(&pixels), 8);
+    }
+
+    #[test]
+    fn ordered_dithering_repeats_every_four_pixels() {
+        let mut pixels = gray(100, 64);
+        dither(&mut pixels, 8, 8, Dithering::Ordered { levels: 2 });
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(pixels[y * 8 + x], pixels[(y % 4) * 8 + x % 4]);
+            }
+        }
+    }
+
+    #[test]
+    fn error_diffusion_keeps_the_average() {
+        let mut pixels = gray(64, 256);
+        dither(&mut pixels, 16, 16, Dithering::ErrorDiffusion { levels: 2 });
+        assert!(pixels.iter().all(|pixel| pixel.r == 0 || pixel.r == 255));
+        let expected = 256.0 * 64.0 / 255.0;
+        assert!((white_count(&pixels) as f32 - expected).abs() <= 2.0);
+    }
+
+    #[test]
+    fn quantized_colors_stay_put() {
+        let mut pixels = vec![Color::new(0, 255, 0), Color::new(255, 0, 255)];
+        let original = pixels.clone();
+        dither(&mut pixels, 2, 1, Dithering::ErrorDiffusion { levels: 2 });
+        assert_eq!(pixels, original);
+    }
+}
@@ -0,0 +1,150 @@
+//! This module shrinks images by averaging blocks of pixels, as needed to get a supersampled
+//! rendering down to its final size.
+
+use crate::{color_space, Color, SaveImageError, ScreenContextManager};
+use std::path::Path;
+
+/// The space pixels are averaged in when downscaling.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Downsampling {
+    /// Convert to linear light before averaging and back to sRGB afterwards, which keeps the
+    /// brightness of anti-aliased edges and fine detail correct.
+    LinearLight,
+    /// Average the sRGB encoded values directly, which is faster but darkens mixes of light and
+    /// dark pixels.
+    Srgb,
+}
+
+impl Downsampling {
+    /// Converts a channel to the space it gets averaged in.
+    pub(crate) fn decode(self, value: u8) -> f32 {
+        match self {
+            Downsampling::LinearLight => color_space::to_linear(value),
+            Downsampling::Srgb => value as f32 / 255.0,
+        }
+    }
+
+    /// Converts an averaged channel back to sRGB.
+    pub(crate) fn encode(self, value: f32) -> u8 {
+        match self {
+            Downsampling::LinearLight => color_space::from_linear(value),
+            Downsampling::Srgb => (value.clamp(0.0, 1.0) * 255.0).round() as u8,
+        }
+    }
+}
+
+/// Shrinks a `width`×`height` image by `factor` in both directions, averaging every
+/// `factor`×`factor` block of pixels into one. Leftover rows and columns at the right and bottom
+/// are dropped.
+pub fn downscale(
+    pixels: &[Color],
+    width: u32,
+    height: u32,
+    factor: u32,
+    mode: Downsampling,
+) -> Vec<Color> {
+    let factor = factor.max(1) as usize;
+    let (width, height) = (width as usize, height as usize);
+    let (target_width, target_height) = (width / factor, height / factor);
+    let area = (factor * factor) as f32;
+
+    let mut target = Vec::with_capacity(target_width * target_height);
+    for y in 0..target_height {
+        for x in 0..target_width {
+            let mut sum = [0.0f32; 3];
+            for source_y in y * factor..(y + 1) * factor {
+                let row =
+                    &pixels[source_y * width + x * factor..source_y * width + (x + 1) * factor];
+                for pixel in row {
+                    sum[0] += mode.decode(pixel.r);
+                    sum[1] += mode.decode(pixel.g);
+                    sum[2] += mode.decode(pixel.b);
+                }
+            }
+            target.push(Color {
+                r: mode.encode(sum[0] / area),
+                g: mode.encode(sum[1] / area),
+                b: mode.encode(sum[2] / area),
+            });
+        }
+    }
+    target
+}
+
+impl ScreenContextManager {
+    /// Chooses how pixels are averaged whenever the framebuffer gets downscaled, and how
+    /// neighboring pixels are mixed when images are scaled with
+    /// [`ScaleFilter::Linear`](crate::ScaleFilter::Linear). The default is
+    /// [`Downsampling::LinearLight`].
+    pub fn set_downsampling(&mut self, mode: Downsampling) {
+        self.downsampling = mode;
+    }
+
+    /// Saves the framebuffer shrunk by `factor` in both directions, e.g. to get a frame rendered
+    /// at twice the intended size down to an anti-aliased image. The format is derived from the
+    /// file extension.
+    pub fn save_img_downscaled<P: AsRef<Path>>(
+        &self,
+        path: P,
+        factor: u32,
+    ) -> Result<(), SaveImageError> {
+        let factor = factor.max(1);
+        let pixels = downscale(
+            &self.framebuffer,
+            self.width,
+            self.height,
+            factor,
+            self.downsampling,
+        );
+        Ok(image::save_buffer(
+            path,
+            bytemuck::cast_slice(&pixels),
+            self.width / factor,
+            self.height / factor,
+            image::ColorType::Rgb8,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Color = Color::new(0, 0, 0);
+    const WHITE: Color = Color::new(255, 255, 255);
+
+    #[test]
+    fn blocks_are_averaged() {
+        let pixels = [BLACK, WHITE, WHITE, WHITE, BLACK, WHITE, WHITE, WHITE];
+        let srgb = downscale(&pixels, 4, 2, 2, Downsampling::Srgb);
+        assert_eq!(srgb, vec![Color::new(128, 128, 128), WHITE]);
+        let linear = downscale(&pixels, 4, 2, 2, Downsampling::LinearLight);
+        assert_eq!(linear, vec![Color::new(188, 188, 188), WHITE]);
+    }
+
+    #[test]
+    fn leftover_rows_and_columns_are_dropped() {
+        let pixels = vec![WHITE; 5 * 3];
+        assert_eq!(
+            downscale(&pixels, 5, 3, 2, Downsampling::Srgb),
+            vec![WHITE; 2]
+        );
+        assert!(downscale(&pixels, 5, 3, 4, Downsampling::Srgb).is_empty());
+    }
+
+    #[test]
+    fn factor_zero_copies_the_image() {
+        let pixels = [BLACK, WHITE, Color::new(10, 20, 30)];
+        let copy = downscale(&pixels, 3, 1, 0, Downsampling::LinearLight);
+        assert_eq!(copy, pixels);
+    }
+
+    #[test]
+    fn channels_survive_a_round_trip() {
+        for mode in [Downsampling::LinearLight, Downsampling::Srgb] {
+            for value in 0..=255 {
+                assert_eq!(mode.encode(mode.decode(value)), value);
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! This module lets other threads queue up drawing for the thread that owns the screen, since
+//! SDL only allows rendering from the main thread.
+
+use crate::ScreenContextManager;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+type DrawCommand = Box<dyn FnOnce(&mut ScreenContextManager) + Send>;
+
+/// A handle for queueing drawing commands from any thread, created with
+/// [`get_draw_queue`](ScreenContextManager::get_draw_queue). The commands run on the screen's
+/// thread, in the order they were pushed, the next time it presents.
+#[derive(Clone)]
+pub struct DrawQueue {
+    sender: Sender<DrawCommand>,
+}
+
+impl DrawQueue {
+    /// Queues `command` to run on the screen. Returns `false` if the screen is gone, in which
+    /// case the command is dropped.
+    pub fn push<F>(&self, command: F) -> bool
+    where
+        F: FnOnce(&mut ScreenContextManager) + Send + 'static,
+    {
+        self.sender.send(Box::new(command)).is_ok()
+    }
+}
+
+/// The receiving end of every [`DrawQueue`] handed out by a screen.
+pub(crate) struct DrawQueueReceiver {
+    sender: Sender<DrawCommand>,
+    receiver: Receiver<DrawCommand>,
+}
+
+impl ScreenContextManager {
+    /// Returns a handle that other threads can queue drawing commands with. All handles feed the
+    /// same queue, which is run by [`present`](ScreenContextManager::present),
+    /// [`present_rect`](ScreenContextManager::present_rect) and
+    /// [`present_while`](ScreenContextManager::present_while) before presenting, never during
+    /// it, or earlier with [`run_draw_queue`](ScreenContextManager::run_draw_queue).
+    pub fn get_draw_queue(&mut self) -> DrawQueue {
+        let queue = self.draw_queue.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            DrawQueueReceiver { sender, receiver }
+        });
+        DrawQueue {
+            sender: queue.sender.clone(),
+        }
+    }
+
+    /// Runs every command queued so far through the handles from
+    /// [`get_draw_queue`](ScreenContextManager::get_draw_queue). Commands queued while they run
+    /// wait for the next time.
+    pub fn run_draw_queue(&mut self) {
+        let commands: Vec<DrawCommand> = match self.draw_queue.as_ref() {
+            Some(queue) => queue.receiver.try_iter().collect(),
+            None => return,
+        };
+        for command in commands {
+            command(self);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! This module exposes the events as an asynchronous stream, for applications with an async main
+//! loop.
+
+use crate::{ScreenContextManager, ScreenEvent};
+use futures_core::Stream;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::mpsc::{self, Sender};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+/// How long an idle stream waits before checking SDL for events again.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A never-ending stream of the events of a screen, created with
+/// [`event_stream`](ScreenContextManager::event_stream).
+pub struct EventStream<'a> {
+    screen: &'a mut ScreenContextManager,
+    buffered: VecDeque<ScreenEvent>,
+    /// Wakes the tasks sent to it after [`POLL_INTERVAL`], started on the first idle poll.
+    /// Its thread ends along with the stream.
+    timer: Option<Sender<Waker>>,
+}
+
+impl Stream for EventStream<'_> {
+    type Item = ScreenEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<ScreenEvent>> {
+        let this = self.get_mut();
+        if this.buffered.is_empty() {
+            this.buffered.extend(this.screen.get_events());
+        }
+        match this.buffered.pop_front() {
+            Some(event) => Poll::Ready(Some(event)),
+            None => {
+                // SDL has no way to notify about new events, and its event queue can only be
+                // read on this thread, so the task is woken up again shortly to check anew
+                let timer = this.timer.get_or_insert_with(|| {
+                    let (sender, wakers) = mpsc::channel::<Waker>();
+                    std::thread::spawn(move || {
+                        for waker in wakers {
+                            std::thread::sleep(POLL_INTERVAL);
+                            waker.wake();
+                        }
+                    });
+                    sender
+                });
+                // The thread only stops once the stream is dropped, so sending can't fail
+                let _ = timer.send(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl ScreenContextManager {
+    /// Returns a stream of the events of this screen, to be awaited one by one with
+    /// `while let Some(event) = stream.next().await`. While no events are pending the stream
+    /// yields to the executor, checking again every few milliseconds so that an idle
+    /// application sleeps instead of spinning.
+    pub fn event_stream(&mut self) -> EventStream<'_> {
+        EventStream {
+            screen: self,
+            buffered: VecDeque::new(),
+            timer: None,
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! This module translates SDL's events into the events the wrapper hands out.
+
+use crate::{ControllerAxis, ControllerButton, Key, ScreenContextManager};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::mouse::{MouseButton as SdlMouseButton, MouseWheelDirection};
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// An event along with when it happened, delivered by
+/// [`get_timed_events`](crate::ScreenContextManager::get_timed_events).
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimedEvent {
+    pub event: ScreenEvent,
+    /// When the event happened, counted from the initialization of SDL with millisecond
+    /// resolution, so it never goes backwards.
+    pub timestamp: Duration,
+    /// Index of the frame being drawn when the event was read, i.e. the amount of frames that had
+    /// been presented before, see
+    /// [`get_frame_index`](crate::ScreenContextManager::get_frame_index).
+    pub frame_index: u64,
+}
+
+/// A button of the mouse.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    /// The first extra button, usually "back".
+    X1,
+    /// The second extra button, usually "forward".
+    X2,
+}
+
+impl MouseButton {
+    fn from_sdl(button: SdlMouseButton) -> Option<MouseButton> {
+        match button {
+            SdlMouseButton::Left => Some(MouseButton::Left),
+            SdlMouseButton::Middle => Some(MouseButton::Middle),
+            SdlMouseButton::Right => Some(MouseButton::Right),
+            SdlMouseButton::X1 => Some(MouseButton::X1),
+            SdlMouseButton::X2 => Some(MouseButton::X2),
+            SdlMouseButton::Unknown => None,
+        }
+    }
+
+    pub(crate) fn to_sdl(self) -> SdlMouseButton {
+        match self {
+            MouseButton::Left => SdlMouseButton::Left,
+            MouseButton::Middle => SdlMouseButton::Middle,
+            MouseButton::Right => SdlMouseButton::Right,
+            MouseButton::X1 => SdlMouseButton::X1,
+            MouseButton::X2 => SdlMouseButton::X2,
+        }
+    }
+}
+
+/// An event delivered by [`get_events`](crate::ScreenContextManager::get_events). New kinds of
+/// events may be added over time, so matches need a catch-all arm.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ScreenEvent {
+    /// The user asked to quit, e.g. by closing the last window.
+    Quit,
+    /// The window changed size, either because the user resized it or because it was resized
+    /// programmatically. The framebuffer keeps its size, call
+    /// [`set_logical_size`](crate::ScreenContextManager::set_logical_size) to follow the window.
+    Resized { width: u32, height: u32 },
+    /// The window gained or lost keyboard focus.
+    FocusChanged { focused: bool },
+    /// The window was uncovered and needs to be presented again, which matters when only
+    /// presenting in response to events.
+    Exposed,
+    /// A key was pressed or released. `repeat` is set on the presses the system synthesizes while
+    /// a key is held down, which can be dropped altogether with
+    /// [`ignore_key_repeats`](crate::ScreenContextManager::ignore_key_repeats).
+    Key {
+        key: Key,
+        pressed: bool,
+        repeat: bool,
+    },
+    /// Text was typed while text input is active, see
+    /// [`start_text_input`](crate::ScreenContextManager::start_text_input). This is the text as
+    /// composed by the keyboard layout or input method, which may be several characters at once.
+    TextInput(String),
+    /// The mouse moved to the given framebuffer pixel, like
+    /// [`get_mouse_position`](crate::ScreenContextManager::get_mouse_position) reports it.
+    MouseMove { x: i32, y: i32 },
+    /// A mouse button was pressed or released with the mouse at the given framebuffer pixel.
+    MouseButton {
+        button: MouseButton,
+        pressed: bool,
+        x: i32,
+        y: i32,
+    },
+    /// The mouse wheel was scrolled, `y` being positive away from the user and `x` to the right,
+    /// regardless of the platform's natural scrolling setting. See also
+    /// [`take_scroll_delta`](crate::ScreenContextManager::take_scroll_delta).
+    Scroll { x: i32, y: i32 },
+    /// A file was dragged onto the window and dropped there.
+    FileDropped(PathBuf),
+    /// A game controller was connected, or was already connected when
+    /// [`enable_controllers`](crate::ScreenContextManager::enable_controllers) was called. `id`
+    /// identifies it until it is disconnected.
+    ControllerConnected { id: u32 },
+    /// The game controller with the given id was disconnected.
+    ControllerDisconnected { id: u32 },
+    /// A button of a game controller was pressed or released.
+    ControllerButton {
+        id: u32,
+        button: ControllerButton,
+        pressed: bool,
+    },
+    /// A stick or trigger of a game controller moved, `value` being in the same range as
+    /// [`get_controller_axis`](crate::ScreenContextManager::get_controller_axis) returns.
+    ControllerAxis {
+        id: u32,
+        axis: ControllerAxis,
+        value: f32,
+    },
+    /// A beat was detected in the audio, see
+    /// [`set_beat_detection`](crate::ScreenContextManager::set_beat_detection). `strength` is how
+    /// many times louder than average the audio got.
+    Beat { strength: f32 },
+    /// The audio level crossed the threshold set with
+    /// [`set_level_threshold`](crate::ScreenContextManager::set_level_threshold), rising above it
+    /// if `above` is set and falling below it otherwise.
+    AudioLevel { level: f32, above: bool },
+}
+
+/// Translates an SDL event, with `to_framebuffer` converting window coordinates to framebuffer
+/// pixels. Events the wrapper has no use for are dropped.
+pub fn translate<F>(event: Event, to_framebuffer: F) -> Option<ScreenEvent>
+where
+    F: Fn(i32, i32) -> (i32, i32),
+{
+    let translated = match event {
+        Event::Quit { .. } => ScreenEvent::Quit,
+        Event::Window { win_event, .. } => match win_event {
+            WindowEvent::SizeChanged(width, height) => ScreenEvent::Resized {
+                width: width.max(0) as u32,
+                height: height.max(0) as u32,
+            },
+            WindowEvent::FocusGained => ScreenEvent::FocusChanged { focused: true },
+            WindowEvent::FocusLost => ScreenEvent::FocusChanged { focused: false },
+            WindowEvent::Exposed => ScreenEvent::Exposed,
+            _ => return None,
+        },
+        Event::KeyDown {
+            keycode: Some(key),
+            repeat,
+            ..
+        } => ScreenEvent::Key {
+            key: Key::from_sdl(key),
+            pressed: true,
+            repeat,
+        },
+        Event::KeyUp {
+            keycode: Some(key),
+            repeat,
+            ..
+        } => ScreenEvent::Key {
+            key: Key::from_sdl(key),
+            pressed: false,
+            repeat,
+        },
+        Event::TextInput { text, .. } => ScreenEvent::TextInput(text),
+        Event::MouseMotion { x, y, .. } => {
+            let (x, y) = to_framebuffer(x, y);
+            ScreenEvent::MouseMove { x, y }
+        }
+        Event::MouseButtonDown {
+            mouse_btn, x, y, ..
+        }
+        | Event::MouseButtonUp {
+            mouse_btn, x, y, ..
+        } => {
+            let (x, y) = to_framebuffer(x, y);
+            ScreenEvent::MouseButton {
+                button: MouseButton::from_sdl(mouse_btn)?,
+                pressed: matches!(event, Event::MouseButtonDown { .. }),
+                x,
+                y,
+            }
+        }
+        Event::MouseWheel {
+            x, y, direction, ..
+        } => match direction {
+            MouseWheelDirection::Flipped => ScreenEvent::Scroll { x: -x, y: -y },
+            _ => ScreenEvent::Scroll { x, y },
+        },
+        Event::DropFile { filename, .. } => ScreenEvent::FileDropped(filename.into()),
+        Event::ControllerButtonDown { which, button, .. } => ScreenEvent::ControllerButton {
+            id: which,
+            button: ControllerButton::from_sdl(button),
+            pressed: true,
+        },
+        Event::ControllerButtonUp { which, button, .. } => ScreenEvent::ControllerButton {
+            id: which,
+            button: ControllerButton::from_sdl(button),
+            pressed: false,
+        },
+        Event::ControllerAxisMotion {
+            which, axis, value, ..
+        } => ScreenEvent::ControllerAxis {
+            id: which,
+            axis: ControllerAxis::from_sdl(axis),
+            value: (value as f32 / i16::MAX as f32).max(-1.0),
+        },
+        _ => return None,
+    };
+    Some(translated)
+}
+
+/// A closure registered with [`on_event`](ScreenContextManager::on_event).
+pub(crate) type EventHandler = Box<dyn FnMut(&ScreenEvent)>;
+
+/// A closure set with [`set_event_filter`](ScreenContextManager::set_event_filter).
+pub(crate) type EventFilter = Box<dyn FnMut(&ScreenEvent) -> bool>;
+
+impl ScreenContextManager {
+    /// Keeps only the latest mouse motion, and the latest motion of every controller axis, out of
+    /// the events read at once. Applications that only care where the mouse ended up each frame
+    /// then don't have to wade through thousands of motion events.
+    pub fn coalesce_motion(&mut self, enabled: bool) {
+        self.coalesce_motion = enabled;
+    }
+
+    /// Drops every event for which `keep` returns `false` before it is delivered.
+    pub fn set_event_filter<F>(&mut self, keep: F)
+    where
+        F: FnMut(&ScreenEvent) -> bool + 'static,
+    {
+        self.event_filter = Some(Box::new(keep));
+    }
+
+    /// Removes the filter set with [`set_event_filter`](ScreenContextManager::set_event_filter).
+    pub fn clear_event_filter(&mut self) {
+        self.event_filter = None;
+    }
+
+    /// Registers a closure to be called with every event read by
+    /// [`pump_events`](ScreenContextManager::pump_events). Several closures can be registered,
+    /// they are called in the order they were registered in.
+    pub fn on_event<F>(&mut self, handler: F)
+    where
+        F: FnMut(&ScreenEvent) + 'static,
+    {
+        self.event_handlers.push(Box::new(handler));
+    }
+
+    /// Reads all pending events and hands each of them to the closures registered with
+    /// [`on_event`](ScreenContextManager::on_event). [`run`](ScreenContextManager::run) calls this
+    /// before every frame when there are any closures registered.
+    pub fn pump_events(&mut self) {
+        let mut handlers = std::mem::take(&mut self.event_handlers);
+        for event in self.get_events() {
+            for handler in handlers.iter_mut() {
+                handler(&event);
+            }
+        }
+        self.event_handlers = handlers;
+    }
+
+    /// Blocks until there is an event, sleeping in the meantime, and returns it. Meant for
+    /// applications that only need to redraw in response to input.
+    pub fn wait_event(&mut self) -> ScreenEvent {
+        loop {
+            if let Some(event) = self.next_ready_event() {
+                return event;
+            }
+            self.events.borrow_mut().wait(self.window_id, None);
+        }
+    }
+
+    /// Like [`wait_event`](ScreenContextManager::wait_event), but giving up and returning `None`
+    /// once `timeout` has passed without events.
+    pub fn wait_event_timeout(&mut self, timeout: Duration) -> Option<ScreenEvent> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(event) = self.next_ready_event() {
+                return Some(event);
+            }
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return None;
+            }
+            self.events.borrow_mut().wait(self.window_id, Some(left));
+        }
+    }
+
+    /// Reads the pending events, keeping all but the first one for later.
+    fn next_ready_event(&mut self) -> Option<ScreenEvent> {
+        if self.ready_events.is_empty() {
+            let events: Vec<TimedEvent> = self.get_timed_events().collect();
+            self.ready_events.extend(events);
+        }
+        self.ready_events.pop_front().map(|timed| timed.event)
+    }
+}
+
+/// Removes all mouse motion events but the last one, and likewise for every controller axis.
+pub fn coalesce_motion(events: &mut VecDeque<Event>) {
+    let mut seen_mouse = false;
+    let mut seen_axes = HashSet::new();
+    // Walking backwards, the first motion of every kind found is the one to keep
+    let keep: Vec<bool> = events
+        .iter()
+        .rev()
+        .map(|event| match *event {
+            Event::MouseMotion { .. } => !std::mem::replace(&mut seen_mouse, true),
+            Event::ControllerAxisMotion { which, axis, .. } => seen_axes.insert((which, axis)),
+            _ => true,
+        })
+        .collect();
+    let mut keep = keep.into_iter().rev();
+    events.retain(|_| keep.next().unwrap_or(true));
+}
+
+/// Time since SDL was initialized, on the same clock as SDL's event timestamps.
+pub fn ticks() -> Duration {
+    // SAFETY: SDL_GetTicks has no preconditions, it even works before SDL is initialized
+    Duration::from_millis(unsafe { sdl2::sys::SDL_GetTicks() } as u64)
+}
@@ -0,0 +1,324 @@
+//! This module implements the fixed-point variants of the rasterizers, used when the raster mode
+//! is [`RasterMode::FixedPoint`](crate::RasterMode::FixedPoint).
+
+use crate::{primitives::Point, Color, FillRule, ScreenContextManager};
+
+/// Fractional bits of the fixed-point coordinates, so they are snapped to 1/256 of a pixel.
+const FRACTION_BITS: u32 = 8;
+/// One pixel in fixed-point units.
+const ONE: i64 = 1 << FRACTION_BITS;
+/// Full coverage of a pixel when blending, in 1/65536 units.
+const FULL_COVERAGE: i64 = ONE * ONE;
+/// Coordinates are clamped to ±2³¹ pixels, way past any framebuffer; f32 can't tell apart points
+/// closer than hundreds of pixels out there anyway. Products of two fixed-point values then fit
+/// comfortably into an i128.
+const MAX_COORDINATE: f32 = (1u64 << 31) as f32;
+
+/// Snaps a coordinate to the fixed-point grid, clamping it to ±[`MAX_COORDINATE`].
+fn to_fixed(value: f32) -> i64 {
+    (value.clamp(-MAX_COORDINATE, MAX_COORDINATE) * ONE as f32).round() as i64
+}
+
+/// Smallest pixel coordinate not below the fixed-point `value`.
+fn ceil_pixel(value: i64) -> i64 {
+    -(-value).div_euclid(ONE)
+}
+
+/// Largest integer not above the square root of `value`, computed bit by bit.
+fn isqrt(value: i128) -> i64 {
+    let mut remainder = value.max(0) as u128;
+    let mut root = 0u128;
+    let mut bit = 1u128 << 126;
+    while bit > remainder {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if remainder >= root + bit {
+            remainder -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root as i64
+}
+
+/// Collects where the edges of the fixed-point `contours` cross the line at height `y`, sorted
+/// from left to right, with +1 for edges going down and -1 for edges going up.
+fn row_crossings(contours: &[Vec<(i64, i64)>], y: i64, crossings: &mut Vec<(i64, i32)>) {
+    crossings.clear();
+    for points in contours {
+        for (i, &a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            // Half-open on y so vertices shared by two edges are only counted once
+            let (winding, top, bottom) = if a.1 < b.1 { (1, a, b) } else { (-1, b, a) };
+            if y >= top.1 && y < bottom.1 {
+                let offset = ((y - top.1) as i128 * (bottom.0 - top.0) as i128)
+                    .div_euclid((bottom.1 - top.1) as i128);
+                crossings.push((top.0 + offset as i64, winding));
+            }
+        }
+    }
+    crossings.sort_by_key(|crossing| crossing.0);
+}
+
+/// Returns the columns from `start` up to but excluding `end` of `row` whose centres lie in the
+/// fixed-point disc, inclusive on both sides like the rows.
+fn disc_span((center_x, center_y): (i64, i64), radius: i64, row: i64) -> (i64, i64) {
+    let dy = (row * ONE - center_y) as i128;
+    let dx = isqrt(radius as i128 * radius as i128 - dy * dy);
+    (
+        ceil_pixel(center_x - dx),
+        (center_x + dx).div_euclid(ONE) + 1,
+    )
+}
+
+impl Color {
+    /// Like [`blend`](Color::blend), but with `coverage` in 1/65536 units and integer math only.
+    fn blend_fixed(self, other: Color, coverage: i64) -> Color {
+        let mix = |from: u8, to: u8| {
+            ((from as i64 * (FULL_COVERAGE - coverage) + to as i64 * coverage + FULL_COVERAGE / 2)
+                >> (2 * FRACTION_BITS)) as u8
+        };
+        Color {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+        }
+    }
+}
+
+impl ScreenContextManager {
+    /// Mixes the current color into the pixel at (`x`, `y`) by `coverage` in 1/65536 units,
+    /// ignoring pixels outside the clip bounds.
+    fn blend_pixel_fixed(&mut self, x: i64, y: i64, coverage: i64) {
+        if coverage <= 0 || !self.clip_bounds().contains(x, y) {
+            return;
+        }
+        let i = (y * self.width as i64 + x) as usize;
+        self.framebuffer[i] =
+            self.framebuffer[i].blend_fixed(self.color, coverage.min(FULL_COVERAGE));
+    }
+
+    /// Fixed-point variant of [`plot_point_f`](ScreenContextManager::plot_point_f).
+    pub(crate) fn plot_point_fixed(&mut self, x: f32, y: f32) {
+        let (x, y) = (to_fixed(x), to_fixed(y));
+        let (left, top) = (x.div_euclid(ONE), y.div_euclid(ONE));
+        let (fx, fy) = (x.rem_euclid(ONE), y.rem_euclid(ONE));
+
+        let corners = [
+            (0, 0, (ONE - fx) * (ONE - fy)),
+            (1, 0, fx * (ONE - fy)),
+            (0, 1, (ONE - fx) * fy),
+            (1, 1, fx * fy),
+        ];
+        for (dx, dy, weight) in corners {
+            self.blend_pixel_fixed(left + dx, top + dy, weight);
+        }
+    }
+
+    /// Fixed-point variant of `fill_contours`, with the crossings of every row computed exactly.
+    pub(crate) fn fill_contours_fixed<C: AsRef<[Point]>>(
+        &mut self,
+        contours: &[C],
+        rule: FillRule,
+    ) {
+        let contours: Vec<Vec<(i64, i64)>> = contours
+            .iter()
+            .map(|contour| {
+                let points = contour.as_ref().iter();
+                points.map(|&(x, y)| (to_fixed(x), to_fixed(y))).collect()
+            })
+            .collect();
+        let all_points = || contours.iter().flatten();
+        let (min_y, max_y) = match (
+            all_points().map(|p| p.1).min(),
+            all_points().map(|p| p.1).max(),
+        ) {
+            (Some(min_y), Some(max_y)) => (min_y, max_y),
+            _ => return,
+        };
+        let first_row = ceil_pixel(min_y).max(0);
+        let last_row = (ceil_pixel(max_y) - 1).min(self.height as i64 - 1);
+
+        let mut crossings: Vec<(i64, i32)> = Vec::new();
+        for row in first_row..=last_row {
+            row_crossings(&contours, row * ONE, &mut crossings);
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                let inside = match rule {
+                    FillRule::NonZero => winding != 0,
+                    FillRule::EvenOdd => winding % 2 != 0,
+                };
+                if inside {
+                    self.fill_span_fixed(row, ceil_pixel(pair[0].0), ceil_pixel(pair[1].0));
+                }
+            }
+        }
+    }
+
+    /// Fills the pixels of `row` from column `start` up to but excluding `end`.
+    fn fill_span_fixed(&mut self, row: i64, start: i64, end: i64) {
+        let clip = self.clip_bounds();
+        let start = start.max(clip.left as i64);
+        let end = end.min(clip.right as i64);
+        if !clip.contains_row(row) || start >= end {
+            return;
+        }
+        let offset = (row * self.width as i64) as usize;
+        self.framebuffer[offset + start as usize..offset + end as usize].fill(self.color);
+    }
+
+    /// Fixed-point variant of `fill_disc`.
+    pub(crate) fn fill_disc_fixed(&mut self, center: Point, radius: f32) {
+        let (center_x, center_y, radius) =
+            (to_fixed(center.0), to_fixed(center.1), to_fixed(radius));
+        let first_row = ceil_pixel(center_y - radius).max(0);
+        let last_row = (center_y + radius)
+            .div_euclid(ONE)
+            .min(self.height as i64 - 1);
+        for row in first_row..=last_row {
+            let (start, end) = disc_span((center_x, center_y), radius, row);
+            self.fill_span_fixed(row, start, end);
+        }
+    }
+
+    /// Fixed-point variant of [`draw_ellipse_aa`](ScreenContextManager::draw_ellipse_aa).
+    pub(crate) fn draw_ellipse_aa_fixed(
+        &mut self,
+        center_x: f32,
+        center_y: f32,
+        radius_x: f32,
+        radius_y: f32,
+    ) {
+        let (center_x, center_y) = (to_fixed(center_x), to_fixed(center_y));
+        let (radius_x, radius_y) = (to_fixed(radius_x), to_fixed(radius_y));
+        if radius_x <= 0 || radius_y <= 0 {
+            return;
+        }
+
+        let (radius_x, radius_y) = (radius_x as i128, radius_y as i128);
+        let diagonal = isqrt(radius_x * radius_x + radius_y * radius_y) as i128;
+        let x_limit = (radius_x * radius_x / diagonal) as i64;
+        let y_limit = (radius_y * radius_y / diagonal) as i64;
+
+        // Only the columns and rows of the framebuffer can be drawn to
+        let first_column = ceil_pixel(center_x - x_limit).max(0);
+        let last_column = (center_x + x_limit)
+            .div_euclid(ONE)
+            .min(self.width as i64 - 1);
+        for column in first_column..=last_column {
+            let dx = (column * ONE - center_x) as i128;
+            let dy = (radius_y * isqrt(radius_x * radius_x - dx * dx) as i128 / radius_x) as i64;
+            for y in [center_y - dy, center_y + dy] {
+                self.plot_wu_step_fixed(column, y, false);
+            }
+        }
+
+        let first_row = ceil_pixel(center_y - y_limit).max(0);
+        let last_row = (center_y + y_limit)
+            .div_euclid(ONE)
+            .min(self.height as i64 - 1);
+        for row in first_row..=last_row {
+            let dy = (row * ONE - center_y) as i128;
+            let dx = (radius_x * isqrt(radius_y * radius_y - dy * dy) as i128 / radius_y) as i64;
+            for x in [center_x - dx, center_x + dx] {
+                self.plot_wu_step_fixed(row, x, true);
+            }
+        }
+    }
+
+    /// Fixed-point variant of `plot_wu_step`.
+    fn plot_wu_step_fixed(&mut self, fixed: i64, along: i64, transposed: bool) {
+        let near = along.div_euclid(ONE);
+        let fraction = along.rem_euclid(ONE);
+
+        for (offset, coverage) in [(0, ONE - fraction), (1, fraction)] {
+            if transposed {
+                self.blend_pixel_fixed(near + offset, fixed, coverage * ONE);
+            } else {
+                self.blend_pixel_fixed(fixed, near + offset, coverage * ONE);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contour(points: &[(f32, f32)]) -> Vec<(i64, i64)> {
+        points
+            .iter()
+            .map(|&(x, y)| (to_fixed(x), to_fixed(y)))
+            .collect()
+    }
+
+    #[test]
+    fn coordinates_snap_and_clamp() {
+        assert_eq!(to_fixed(1.5), 384);
+        assert_eq!(to_fixed(-0.25), -64);
+        assert_eq!(to_fixed(1e30), (MAX_COORDINATE * ONE as f32) as i64);
+        assert_eq!(to_fixed(-1e30), -(MAX_COORDINATE * ONE as f32) as i64);
+        assert_eq!(ceil_pixel(ONE), 1);
+        assert_eq!(ceil_pixel(ONE + 1), 2);
+        assert_eq!(ceil_pixel(-ONE + 1), 0);
+    }
+
+    #[test]
+    fn isqrt_rounds_down() {
+        for value in [0i128, 1, 2, 3, 4, 15, 16, 17, 1 << 40, (1 << 40) - 1] {
+            let root = isqrt(value) as i128;
+            assert!(root * root <= value && (root + 1) * (root + 1) > value);
+        }
+        assert_eq!(isqrt(-5), 0);
+        let big = to_fixed(MAX_COORDINATE) as i128;
+        assert_eq!(isqrt(big * big), big as i64);
+    }
+
+    #[test]
+    fn square_crossings() {
+        let square = [contour(&[(1.0, 1.0), (4.5, 1.0), (4.5, 3.0), (1.0, 3.0)])];
+        let mut crossings = Vec::new();
+        row_crossings(&square, 2 * ONE, &mut crossings);
+        assert_eq!(crossings, vec![(ONE, -1), (4 * ONE + ONE / 2, 1)]);
+        // The bottom edge is excluded, the top one included
+        row_crossings(&square, 3 * ONE, &mut crossings);
+        assert!(crossings.is_empty());
+        row_crossings(&square, ONE, &mut crossings);
+        assert_eq!(crossings.len(), 2);
+    }
+
+    #[test]
+    fn far_contours_cross_without_overflowing() {
+        let far = MAX_COORDINATE * 4.0;
+        let triangle = contour(&[(-far, -far), (far, 0.0), (-far, far)]);
+        let mut crossings = Vec::new();
+        row_crossings(&[triangle], 0, &mut crossings);
+        let max = to_fixed(MAX_COORDINATE);
+        assert_eq!(crossings, vec![(-max, -1), (max, 1)]);
+    }
+
+    #[test]
+    fn disc_spans() {
+        let center = (to_fixed(5.0), to_fixed(5.0));
+        assert_eq!(disc_span(center, to_fixed(2.0), 5), (3, 8));
+        assert_eq!(disc_span(center, to_fixed(2.0), 3), (5, 6));
+        assert_eq!(disc_span(center, to_fixed(2.0), 4), (4, 7));
+    }
+
+    #[test]
+    fn blending_is_exact_at_the_ends() {
+        let black = Color::new(0, 0, 0);
+        let white = Color::new(255, 255, 255);
+        assert_eq!(black.blend_fixed(white, 0), black);
+        assert_eq!(black.blend_fixed(white, FULL_COVERAGE), white);
+        assert_eq!(
+            black.blend_fixed(white, FULL_COVERAGE / 2),
+            Color::new(128, 128, 128)
+        );
+    }
+}
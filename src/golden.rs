@@ -0,0 +1,109 @@
+//! This module provides a golden-image test runner, so renderers built on top of this crate can
+//! get regression suites with almost no harness code.
+
+use crate::{GoldenError, ScreenContextManager};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+
+/// Environment variable that, when set to anything other than `0`, makes the suite overwrite the
+/// golden images with the freshly rendered ones instead of comparing against them.
+pub const BLESS_VAR: &str = "SDL_WRAPPER_BLESS";
+
+type DrawCase = Box<dyn FnMut(&mut ScreenContextManager)>;
+
+/// Held while a suite runs, since SDL can only be initialized once at a time per process.
+static SUITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// A list of named draw closures whose output is compared against `<name>.png` files in a golden
+/// directory. The closures run on a [headless](ScreenContextManager::headless) screen, so no
+/// display server is needed.
+///
+/// Suites in the same test binary take turns, but SDL can't be used by anything else while one
+/// is running, so other tests creating screens need a binary of their own or
+/// `--test-threads=1`.
+///
+/// ```no_run
+/// use sdl_wrapper::GoldenSuite;
+///
+/// GoldenSuite::new("tests/golden", 64, 64)
+///     .case("red_pixel", |screen| {
+///         screen.set_color(1.0, 0.0, 0.0);
+///         screen.plot_pixel(10, 10);
+///     })
+///     .run()
+///     .unwrap();
+/// ```
+pub struct GoldenSuite {
+    dir: PathBuf,
+    width: u32,
+    height: u32,
+    cases: Vec<(String, DrawCase)>,
+}
+
+impl GoldenSuite {
+    /// Creates an empty suite whose goldens live in `dir`, rendering into a `width`×`height`
+    /// framebuffer.
+    pub fn new<P: Into<PathBuf>>(dir: P, width: u32, height: u32) -> GoldenSuite {
+        GoldenSuite {
+            dir: dir.into(),
+            width,
+            height,
+            cases: Vec::new(),
+        }
+    }
+
+    /// Adds a case to the suite. Every case gets a screen of its own, starting from a black
+    /// framebuffer, black color and default settings whatever the other cases changed.
+    pub fn case<F>(mut self, name: &str, draw: F) -> GoldenSuite
+    where
+        F: FnMut(&mut ScreenContextManager) + 'static,
+    {
+        self.cases.push((name.to_string(), Box::new(draw)));
+        self
+    }
+
+    /// Renders every case and compares it with its golden, or overwrites the goldens when
+    /// blessing. On a mismatch the rendered image is saved next to the golden as
+    /// `<name>.actual.png` for inspection.
+    pub fn run(mut self) -> Result<(), GoldenError> {
+        // A case that panicked in another suite doesn't leave SDL in use
+        let _running = SUITE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let bless = std::env::var_os(BLESS_VAR).is_some_and(|value| value != "0");
+        fs::create_dir_all(&self.dir)?;
+
+        let mut missing = Vec::new();
+        let mut mismatched = Vec::new();
+        for (name, draw) in self.cases.iter_mut() {
+            let mut screen = ScreenContextManager::headless(self.width, self.height)?;
+            draw(&mut screen);
+
+            let golden_path = self.dir.join(format!("{}.png", name));
+            if bless {
+                screen.save_img(&golden_path)?;
+                continue;
+            }
+            if !golden_path.exists() {
+                missing.push(name.clone());
+                continue;
+            }
+
+            let golden = image::open(&golden_path)?.to_rgb8();
+            let rendered: &[u8] = bytemuck::cast_slice(&screen.framebuffer);
+            if golden.dimensions() != (self.width, self.height) || golden.as_raw() != rendered {
+                let actual_path = self.dir.join(format!("{}.actual.png", name));
+                screen.save_img(actual_path)?;
+                mismatched.push(name.clone());
+            }
+        }
+
+        if !missing.is_empty() {
+            Err(GoldenError::Missing(missing))
+        } else if !mismatched.is_empty() {
+            Err(GoldenError::Mismatch(mismatched))
+        } else {
+            Ok(())
+        }
+    }
+}
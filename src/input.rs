@@ -0,0 +1,70 @@
+//! This module lets the current state of the input devices be polled, independently of the
+//! events reporting their changes.
+
+use crate::{Key, MouseButton, ScreenContextManager};
+use sdl2::keyboard::Scancode;
+
+impl ScreenContextManager {
+    /// Returns whether the given key is being held down right now. SDL updates this state while
+    /// events are processed, so it is current as of the last
+    /// [`get_events`](ScreenContextManager::get_events).
+    pub fn is_key_down(&self, key: Key) -> bool {
+        Scancode::from_keycode(key.to_sdl())
+            .is_some_and(|scancode| self.events.borrow().is_scancode_pressed(scancode))
+    }
+
+    /// Returns the position of the mouse in framebuffer pixels, taking the scale mode and logical
+    /// size into account. The position lies outside of the framebuffer when the mouse is over the
+    /// borders around it, and is only current while the window has mouse focus.
+    pub fn get_mouse_position(&self) -> (i32, i32) {
+        let state = self.events.borrow().mouse_state();
+        self.output
+            .window_to_framebuffer(state.x(), state.y(), self.width, self.height)
+    }
+
+    /// Returns whether the given mouse button is being held down right now.
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.events
+            .borrow()
+            .mouse_state()
+            .is_mouse_button_pressed(button.to_sdl())
+    }
+
+    /// Returns how far the mouse wheel was scrolled in the events read by
+    /// [`get_events`](ScreenContextManager::get_events) since the last call, as in
+    /// [`ScreenEvent::Scroll`](crate::ScreenEvent::Scroll), and starts counting from zero again.
+    pub fn take_scroll_delta(&mut self) -> (i32, i32) {
+        std::mem::take(&mut self.scroll_delta)
+    }
+
+    /// Captures the mouse: the cursor is hidden and kept inside the window, while motion keeps
+    /// being reported through [`take_mouse_delta`](ScreenContextManager::take_mouse_delta) even
+    /// where the cursor would hit the window's edge. Meant for first-person camera controls.
+    pub fn set_relative_mouse_mode(&mut self, enabled: bool) {
+        let mouse = self.output.canvas.window().subsystem().sdl().mouse();
+        mouse.set_relative_mouse_mode(enabled);
+    }
+
+    /// Returns how far the mouse moved, in window coordinates, since the last call.
+    pub fn take_mouse_delta(&mut self) -> (i32, i32) {
+        let state = self.events.borrow().relative_mouse_state();
+        (state.x(), state.y())
+    }
+
+    /// Starts delivering typed text as [`ScreenEvent::TextInput`](crate::ScreenEvent::TextInput)
+    /// events, opening the on-screen keyboard or input method window where there is one.
+    pub fn start_text_input(&mut self) {
+        self.output.canvas.window().subsystem().text_input().start();
+    }
+
+    /// Stops delivering typed text.
+    pub fn stop_text_input(&mut self) {
+        self.output.canvas.window().subsystem().text_input().stop();
+    }
+
+    /// Drops the key presses the system repeats while a key is held down, so that every
+    /// [`ScreenEvent::Key`](crate::ScreenEvent::Key) press is a real one.
+    pub fn ignore_key_repeats(&mut self, ignore: bool) {
+        self.ignore_key_repeats = ignore;
+    }
+}
@@ -0,0 +1,111 @@
+//! This module gives raw access to joysticks, for devices that don't map onto a game controller
+//! like flight sticks and wheels.
+
+use crate::{InitError, ScreenContextManager};
+use sdl2::{event::Event, joystick::Joystick, JoystickSubsystem};
+
+/// The joysticks opened so far.
+pub(crate) struct Joysticks {
+    subsystem: JoystickSubsystem,
+    open: Vec<Joystick>,
+}
+
+impl Joysticks {
+    /// Opens the joystick at the given device index, unless it is open already.
+    fn open(&mut self, index: u32) {
+        if let Ok(joystick) = self.subsystem.open(index) {
+            let id = joystick.instance_id();
+            if self.open.iter().all(|open| open.instance_id() != id) {
+                self.open.push(joystick);
+            }
+        }
+    }
+
+    /// Opens or closes joysticks on connection events.
+    pub fn handle(&mut self, event: &Event) {
+        match *event {
+            Event::JoyDeviceAdded { which, .. } => self.open(which),
+            Event::JoyDeviceRemoved { which, .. } => {
+                self.open.retain(|joystick| joystick.instance_id() != which)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The state of a joystick when it was polled with
+/// [`get_joysticks`](ScreenContextManager::get_joysticks).
+#[derive(Clone, Debug, PartialEq)]
+pub struct JoystickState {
+    /// Identifies the joystick for as long as it stays connected.
+    pub id: u32,
+    pub name: String,
+    /// Position of every axis in the range [-1, 1].
+    pub axes: Vec<f32>,
+    pub buttons: Vec<bool>,
+    /// Direction of every hat switch as (x, y), each -1, 0 or 1 with y negative being up.
+    pub hats: Vec<(i8, i8)>,
+}
+
+impl JoystickState {
+    fn poll(joystick: &Joystick) -> JoystickState {
+        let axes = (0..joystick.num_axes())
+            .map(|axis| joystick.axis(axis).unwrap_or(0) as f32 / i16::MAX as f32)
+            .map(|value| value.max(-1.0))
+            .collect();
+        let buttons = (0..joystick.num_buttons())
+            .map(|button| joystick.button(button).unwrap_or(false))
+            .collect();
+        let hats = (0..joystick.num_hats())
+            .map(|hat| {
+                use sdl2::joystick::HatState::*;
+                match joystick.hat(hat) {
+                    Ok(LeftUp) => (-1, -1),
+                    Ok(Up) => (0, -1),
+                    Ok(RightUp) => (1, -1),
+                    Ok(Left) => (-1, 0),
+                    Ok(Right) => (1, 0),
+                    Ok(LeftDown) => (-1, 1),
+                    Ok(Down) => (0, 1),
+                    Ok(RightDown) => (1, 1),
+                    Ok(Centered) | Err(_) => (0, 0),
+                }
+            })
+            .collect();
+        JoystickState {
+            id: joystick.instance_id(),
+            name: joystick.name(),
+            axes,
+            buttons,
+            hats,
+        }
+    }
+}
+
+impl ScreenContextManager {
+    /// Opens all connected joysticks, and keeps opening the ones connected later while events are
+    /// read. Game controllers are joysticks too, so they show up here as well.
+    pub fn enable_joysticks(&mut self) -> Result<(), InitError> {
+        if self.joysticks.is_none() {
+            let sdl = self.output.canvas.window().subsystem().sdl();
+            let mut joysticks = Joysticks {
+                subsystem: sdl.joystick()?,
+                open: Vec::new(),
+            };
+            for index in 0..joysticks.subsystem.num_joysticks()? {
+                joysticks.open(index);
+            }
+            self.joysticks = Some(joysticks);
+        }
+        Ok(())
+    }
+
+    /// Polls the current state of every open joystick.
+    pub fn get_joysticks(&self) -> impl Iterator<Item = JoystickState> + '_ {
+        let open = self
+            .joysticks
+            .iter()
+            .flat_map(|joysticks| joysticks.open.iter());
+        open.map(JoystickState::poll)
+    }
+}
@@ -0,0 +1,231 @@
+//! This module defines the keys and game controller inputs handed out by the wrapper, so that
+//! applications don't depend on SDL's own types and keep building when SDL's bindings change.
+
+use sdl2::controller::{Axis, Button};
+use sdl2::keyboard::Keycode;
+
+/// Declares [`Key`] along with its conversions from and to SDL's keycodes, which use the same
+/// names.
+macro_rules! keys {
+    ($($key:ident),* $(,)?) => {
+        /// A key of the keyboard, named after what it types in the current keyboard layout rather
+        /// than where it sits. More keys may be added over time, so matches need a catch-all arm.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum Key {
+            $($key),*
+        }
+
+        impl Key {
+            pub(crate) fn from_sdl(key: Keycode) -> Key {
+                match key {
+                    $(Keycode::$key => Key::$key),*
+                }
+            }
+
+            pub(crate) fn to_sdl(self) -> Keycode {
+                match self {
+                    $(Key::$key => Keycode::$key),*
+                }
+            }
+        }
+
+        #[cfg(test)]
+        const ALL_KEYS: &[Key] = &[$(Key::$key),*];
+    };
+}
+
+keys! {
+    Backspace, Tab, Return, Escape, Space, Exclaim, Quotedbl, Hash, Dollar, Percent, Ampersand,
+    Quote, LeftParen, RightParen, Asterisk, Plus, Comma, Minus, Period, Slash, Num0, Num1, Num2,
+    Num3, Num4, Num5, Num6, Num7, Num8, Num9, Colon, Semicolon, Less, Equals, Greater, Question, At,
+    LeftBracket, Backslash, RightBracket, Caret, Underscore, Backquote, A, B, C, D, E, F, G, H, I,
+    J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Delete, CapsLock, F1, F2, F3, F4, F5, F6, F7,
+    F8, F9, F10, F11, F12, PrintScreen, ScrollLock, Pause, Insert, Home, PageUp, End, PageDown,
+    Right, Left, Down, Up, NumLockClear, KpDivide, KpMultiply, KpMinus, KpPlus, KpEnter, Kp1, Kp2,
+    Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9, Kp0, KpPeriod, Application, Power, KpEquals, F13, F14, F15,
+    F16, F17, F18, F19, F20, F21, F22, F23, F24, Execute, Help, Menu, Select, Stop, Again, Undo,
+    Cut, Copy, Paste, Find, Mute, VolumeUp, VolumeDown, KpComma, KpEqualsAS400, AltErase, Sysreq,
+    Cancel, Clear, Prior, Return2, Separator, Out, Oper, ClearAgain, CrSel, ExSel, Kp00, Kp000,
+    ThousandsSeparator, DecimalSeparator, CurrencyUnit, CurrencySubUnit, KpLeftParen, KpRightParen,
+    KpLeftBrace, KpRightBrace, KpTab, KpBackspace, KpA, KpB, KpC, KpD, KpE, KpF, KpXor, KpPower,
+    KpPercent, KpLess, KpGreater, KpAmpersand, KpDblAmpersand, KpVerticalBar, KpDblVerticalBar,
+    KpColon, KpHash, KpSpace, KpAt, KpExclam, KpMemStore, KpMemRecall, KpMemClear, KpMemAdd,
+    KpMemSubtract, KpMemMultiply, KpMemDivide, KpPlusMinus, KpClear, KpClearEntry, KpBinary,
+    KpOctal, KpDecimal, KpHexadecimal, LCtrl, LShift, LAlt, LGui, RCtrl, RShift, RAlt, RGui, Mode,
+    AudioNext, AudioPrev, AudioStop, AudioPlay, AudioMute, MediaSelect, Www, Mail, Calculator,
+    Computer, AcSearch, AcHome, AcBack, AcForward, AcStop, AcRefresh, AcBookmarks, BrightnessDown,
+    BrightnessUp, DisplaySwitch, KbdIllumToggle, KbdIllumDown, KbdIllumUp, Eject, Sleep,
+}
+
+/// A button of a game controller, named after its position on an Xbox controller.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ControllerButton {
+    A,
+    B,
+    X,
+    Y,
+    Back,
+    Guide,
+    Start,
+    LeftStick,
+    RightStick,
+    LeftShoulder,
+    RightShoulder,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    /// Share, capture or microphone button, depending on the controller.
+    Misc1,
+    Paddle1,
+    Paddle2,
+    Paddle3,
+    Paddle4,
+    Touchpad,
+}
+
+impl ControllerButton {
+    pub(crate) fn from_sdl(button: Button) -> ControllerButton {
+        match button {
+            Button::A => ControllerButton::A,
+            Button::B => ControllerButton::B,
+            Button::X => ControllerButton::X,
+            Button::Y => ControllerButton::Y,
+            Button::Back => ControllerButton::Back,
+            Button::Guide => ControllerButton::Guide,
+            Button::Start => ControllerButton::Start,
+            Button::LeftStick => ControllerButton::LeftStick,
+            Button::RightStick => ControllerButton::RightStick,
+            Button::LeftShoulder => ControllerButton::LeftShoulder,
+            Button::RightShoulder => ControllerButton::RightShoulder,
+            Button::DPadUp => ControllerButton::DPadUp,
+            Button::DPadDown => ControllerButton::DPadDown,
+            Button::DPadLeft => ControllerButton::DPadLeft,
+            Button::DPadRight => ControllerButton::DPadRight,
+            Button::Misc1 => ControllerButton::Misc1,
+            Button::Paddle1 => ControllerButton::Paddle1,
+            Button::Paddle2 => ControllerButton::Paddle2,
+            Button::Paddle3 => ControllerButton::Paddle3,
+            Button::Paddle4 => ControllerButton::Paddle4,
+            Button::Touchpad => ControllerButton::Touchpad,
+        }
+    }
+
+    pub(crate) fn to_sdl(self) -> Button {
+        match self {
+            ControllerButton::A => Button::A,
+            ControllerButton::B => Button::B,
+            ControllerButton::X => Button::X,
+            ControllerButton::Y => Button::Y,
+            ControllerButton::Back => Button::Back,
+            ControllerButton::Guide => Button::Guide,
+            ControllerButton::Start => Button::Start,
+            ControllerButton::LeftStick => Button::LeftStick,
+            ControllerButton::RightStick => Button::RightStick,
+            ControllerButton::LeftShoulder => Button::LeftShoulder,
+            ControllerButton::RightShoulder => Button::RightShoulder,
+            ControllerButton::DPadUp => Button::DPadUp,
+            ControllerButton::DPadDown => Button::DPadDown,
+            ControllerButton::DPadLeft => Button::DPadLeft,
+            ControllerButton::DPadRight => Button::DPadRight,
+            ControllerButton::Misc1 => Button::Misc1,
+            ControllerButton::Paddle1 => Button::Paddle1,
+            ControllerButton::Paddle2 => Button::Paddle2,
+            ControllerButton::Paddle3 => Button::Paddle3,
+            ControllerButton::Paddle4 => Button::Paddle4,
+            ControllerButton::Touchpad => Button::Touchpad,
+        }
+    }
+}
+
+/// A stick or trigger of a game controller.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ControllerAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    TriggerLeft,
+    TriggerRight,
+}
+
+impl ControllerAxis {
+    pub(crate) fn from_sdl(axis: Axis) -> ControllerAxis {
+        match axis {
+            Axis::LeftX => ControllerAxis::LeftX,
+            Axis::LeftY => ControllerAxis::LeftY,
+            Axis::RightX => ControllerAxis::RightX,
+            Axis::RightY => ControllerAxis::RightY,
+            Axis::TriggerLeft => ControllerAxis::TriggerLeft,
+            Axis::TriggerRight => ControllerAxis::TriggerRight,
+        }
+    }
+
+    pub(crate) fn to_sdl(self) -> Axis {
+        match self {
+            ControllerAxis::LeftX => Axis::LeftX,
+            ControllerAxis::LeftY => Axis::LeftY,
+            ControllerAxis::RightX => Axis::RightX,
+            ControllerAxis::RightY => Axis::RightY,
+            ControllerAxis::TriggerLeft => Axis::TriggerLeft,
+            ControllerAxis::TriggerRight => Axis::TriggerRight,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_match_sdl_by_name() {
+        for &key in ALL_KEYS {
+            assert_eq!(format!("{:?}", key), format!("{:?}", key.to_sdl()));
+            assert_eq!(Key::from_sdl(key.to_sdl()), key);
+        }
+    }
+
+    #[test]
+    fn controller_buttons_match_sdl_by_name() {
+        use ControllerButton::*;
+        let buttons = [
+            A,
+            B,
+            X,
+            Y,
+            Back,
+            Guide,
+            Start,
+            LeftStick,
+            RightStick,
+            LeftShoulder,
+            RightShoulder,
+            DPadUp,
+            DPadDown,
+            DPadLeft,
+            DPadRight,
+            Misc1,
+            Paddle1,
+            Paddle2,
+            Paddle3,
+            Paddle4,
+            Touchpad,
+        ];
+        for button in buttons {
+            assert_eq!(format!("{:?}", button), format!("{:?}", button.to_sdl()));
+            assert_eq!(ControllerButton::from_sdl(button.to_sdl()), button);
+        }
+    }
+
+    #[test]
+    fn controller_axes_match_sdl_by_name() {
+        use ControllerAxis::*;
+        for axis in [LeftX, LeftY, RightX, RightY, TriggerLeft, TriggerRight] {
+            assert_eq!(format!("{:?}", axis), format!("{:?}", axis.to_sdl()));
+            assert_eq!(ControllerAxis::from_sdl(axis.to_sdl()), axis);
+        }
+    }
+}
@@ -1,68 +1,371 @@
 //! This module provides simple wrapper functions that will be the only interactions the rest of
 //! the project has with the [sdl2](sdl2) crate.
 
+mod animation;
+mod audio;
+mod backend;
+mod batch;
+mod blit;
+mod builder;
+mod camera;
+mod capture;
+mod clip;
+mod clipboard;
+mod color_space;
+mod console;
 mod constants;
-use crate::constants::COLOR_DEPTH;
+mod context;
+mod controller;
+mod crash;
+mod cursor;
+mod depth;
+mod dither;
+mod downscale;
+mod draw_queue;
+mod event_stream;
+mod events;
+mod fixed;
+mod golden;
+mod input;
+mod joystick;
+mod keys;
+mod main_loop;
+mod native;
+mod output;
+mod overlay;
+mod primitives;
+mod recording;
+mod scanlines;
+mod sharpen;
+mod snapshot;
+mod sound;
+mod sprite;
+mod surface;
+#[cfg(feature = "svg")]
+mod svg;
+mod text;
+mod text_grid;
+mod tilemap;
+mod timers;
+mod timing;
+#[cfg(feature = "ttf")]
+mod ttf;
+mod vector_animation;
+mod window;
+use crate::audio::AudioTriggers;
+use crate::batch::Span;
+use crate::camera::Camera;
+use crate::console::Console;
+use crate::context::EventRouter;
+use crate::controller::Controllers;
+use crate::crash::CrashReporter;
+use crate::draw_queue::DrawQueueReceiver;
+use crate::events::{EventFilter, EventHandler};
+use crate::joystick::Joysticks;
+use crate::output::Output;
+use crate::recording::Recording;
+use crate::sound::Speaker;
+use crate::surface::SavedTarget;
+use crate::timers::Timers;
+use crate::timing::{FramePacer, FrameTimer};
 
 use bytemuck::{self, Pod, Zeroable};
-use image;
-use sdl2::{
-    pixels::PixelFormatEnum,
-    render::{Canvas, TextureCreator},
-    video::{Window, WindowContext},
-    EventPump,
-};
+use sdl2::event::Event;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
-pub use sdl2::{
-    event::{Event, EventPollIterator},
-    keyboard::Keycode,
+pub use crate::animation::Animation;
+pub use crate::audio::AudioTap;
+pub use crate::backend::{Backend, DrawCall, MockBackend};
+pub use crate::batch::Batch;
+pub use crate::blit::Image;
+pub use crate::builder::ScreenBuilder;
+pub use crate::camera::Origin;
+pub use crate::capture::Capture;
+pub use crate::color_space::ColorSpace;
+pub use crate::context::SdlContext;
+pub use crate::cursor::CursorSprite;
+pub use crate::dither::Dithering;
+pub use crate::downscale::Downsampling;
+pub use crate::draw_queue::DrawQueue;
+pub use crate::event_stream::EventStream;
+pub use crate::events::{MouseButton, ScreenEvent, TimedEvent};
+pub use crate::golden::GoldenSuite;
+pub use crate::joystick::JoystickState;
+pub use crate::keys::{ControllerAxis, ControllerButton, Key};
+pub use crate::main_loop::{IdleMode, LoopAction};
+pub use crate::native::NativeWindow;
+pub use crate::output::{ScaleFilter, ScaleMode};
+pub use crate::primitives::{FillRule, LineCap, LineJoin, RasterMode, StrokeStyle};
+pub use crate::recording::RawFormat;
+pub use crate::snapshot::FrameSnapshot;
+pub use crate::sound::{Sound, VoiceId, Waveform};
+pub use crate::sprite::Sprite;
+pub use crate::surface::Surface;
+pub use crate::text_grid::TextGrid;
+pub use crate::tilemap::{SpriteSheet, TileMap};
+pub use crate::timers::TimerId;
+pub use crate::timing::FrameStats;
+#[cfg(feature = "ttf")]
+pub use crate::ttf::Font;
+pub use crate::vector_animation::{
+    Animatable, AnimatedShape, Easing, Keyframes, ShapeKind, VectorAnimation,
 };
+pub use crate::window::FullscreenMode;
 
-#[derive(Copy, Clone, Pod, Zeroable)]
+/// The color of one pixel of the framebuffer, laid out as in the raw RGB24 bytes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
 #[repr(C)]
-struct Pixel {
-    r: u8,
-    g: u8,
-    b: u8,
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b }
+    }
+
+    /// Mixes `other` over this color, `alpha` being how much of `other` ends up in the result in
+    /// the range [0, 1].
+    pub(crate) fn blend(self, other: Color, alpha: f32) -> Color {
+        let mix =
+            |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * alpha).round() as u8;
+        Color {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+        }
+    }
 }
 
+/// Options used when creating the window, see
+/// [`with_options`](ScreenContextManager::with_options).
+#[derive(Clone, Debug, Default)]
+pub struct WindowOptions {
+    /// Synchronize presentation with the display's refresh rate to avoid tearing.
+    pub vsync: bool,
+    /// Let the user resize the window, see [`ScreenEvent::Resized`].
+    pub resizable: bool,
+    /// Create the window without decorations (title bar, borders).
+    pub borderless: bool,
+    /// Keep the window above all other windows.
+    pub always_on_top: bool,
+    /// Create the window hidden, so that it can be shown once the first frame is ready.
+    pub hidden: bool,
+    /// Start out fullscreen.
+    pub fullscreen: FullscreenMode,
+}
+
+/// A closure set with [`set_post_process`](ScreenContextManager::set_post_process).
+type PostProcess = Box<dyn FnMut(&mut [u8], u32, u32)>;
+
 /// This struct abstracts away any direct interaction with the SDL module, so that the user may
 /// only need to call the provided methods without `use`ing any sdl modules.
 pub struct ScreenContextManager {
-    canvas: Canvas<Window>,
-    framebuffer: Vec<Pixel>,
-    texture_creator: TextureCreator<WindowContext>,
-    color: Pixel,
-    event_pump: EventPump,
+    output: Output,
+    backend: Option<Box<dyn Backend>>,
+    framebuffer: Vec<Color>,
+    front_buffer: Option<Vec<Color>>,
+    hold_frames: bool,
+    hold_spinner_after: Option<Duration>,
+    color: Color,
+    clip_rect: Option<(u32, u32, u32, u32)>,
+    camera: Camera,
+    origin: Origin,
+    color_space: ColorSpace,
+    depth_buffer: Option<Vec<f32>>,
+    events: Rc<RefCell<EventRouter>>,
+    window_id: u32,
+    pending_events: VecDeque<Event>,
+    scroll_delta: (i32, i32),
+    ignore_key_repeats: bool,
+    event_handlers: Vec<EventHandler>,
+    ready_events: VecDeque<TimedEvent>,
+    coalesce_motion: bool,
+    event_filter: Option<EventFilter>,
+    controllers: Option<Controllers>,
+    joysticks: Option<Joysticks>,
+    idle_mode: IdleMode,
+    timers: Timers,
+    recent_events: Option<VecDeque<Event>>,
+    crash_reporter: Option<CrashReporter>,
+    frame_index: u64,
+    pacer: Option<FramePacer>,
+    frame_timer: FrameTimer,
+    show_fps: bool,
+    console: Console,
+    software_cursor: Option<CursorSprite>,
+    cursor_image: Option<sdl2::mouse::Cursor>,
+    sharpening: Option<f32>,
+    post_process: Option<PostProcess>,
+    dithering: Option<Dithering>,
+    downsampling: Downsampling,
+    staging: Vec<Color>,
+    batch_spans: Vec<Span>,
+    batch_pixels: Vec<(usize, Color)>,
+    draw_queue: Option<DrawQueueReceiver>,
+    render_targets: Vec<SavedTarget>,
+    raster_mode: RasterMode,
+    alt_enter_fullscreen: Option<FullscreenMode>,
+    animation_time: Duration,
+    audio_tap: Option<AudioTap>,
+    speaker: Option<Speaker>,
+    audio_samples: Vec<f32>,
+    audio_spectrum: Vec<f32>,
+    audio_triggers: AudioTriggers,
+    audio_events: VecDeque<(Duration, ScreenEvent)>,
+    pending_captures: Vec<Capture>,
+    captures: Vec<Capture>,
+    recording: Option<Recording>,
     height: u32,
     width: u32,
-    width_times_color: u32,
 }
 
 impl ScreenContextManager {
-    /// Creates a new object, with the side-effect of creating a new window with the title given.
+    /// Creates a new object, with the side-effect of initializing SDL and creating a new window
+    /// with the title given. To open more than one window, create them from a shared
+    /// [`SdlContext`] instead.
     pub fn new(title: &str, width: u32, height: u32) -> Result<ScreenContextManager, InitError> {
-        let sdl = sdl2::init()?;
-        let video_subsystem = sdl.video()?;
-        let window = video_subsystem.window(title, width, height).build()?;
+        Self::with_options(title, width, height, WindowOptions::default())
+    }
+
+    /// Like [`new`](ScreenContextManager::new), but creating the window with the given options.
+    pub fn with_options(
+        title: &str,
+        width: u32,
+        height: u32,
+        options: WindowOptions,
+    ) -> Result<ScreenContextManager, InitError> {
+        SdlContext::new()?.create_window(title, width, height, options)
+    }
+
+    /// Creates the screen, with a hidden window and a software renderer when `offscreen` is set so
+    /// that it also works on SDL's dummy video driver.
+    pub(crate) fn create(
+        context: &SdlContext,
+        title: &str,
+        width: u32,
+        height: u32,
+        options: WindowOptions,
+        offscreen: bool,
+    ) -> Result<ScreenContextManager, InitError> {
+        let mut window_builder = context.video().window(title, width, height);
+        if offscreen || options.hidden {
+            window_builder.hidden();
+        }
+        if options.resizable {
+            window_builder.resizable();
+        }
+        if options.borderless {
+            window_builder.borderless();
+        }
+        match options.fullscreen {
+            FullscreenMode::Off => {}
+            FullscreenMode::Desktop => {
+                window_builder.fullscreen_desktop();
+            }
+            FullscreenMode::Exclusive => {
+                window_builder.fullscreen();
+            }
+        }
+        if options.always_on_top {
+            let flags = window_builder.window_flags()
+                | sdl2::sys::SDL_WindowFlags::SDL_WINDOW_ALWAYS_ON_TOP as u32;
+            window_builder.set_window_flags(flags);
+        }
+        let window = window_builder.build()?;
+        Self::from_window(context, window, width, height, options.vsync, offscreen)
+    }
 
-        let canvas = window.into_canvas().accelerated().build()?;
+    /// Creates the screen around an existing window, with a framebuffer of the given size.
+    pub(crate) fn from_window(
+        context: &SdlContext,
+        window: sdl2::video::Window,
+        width: u32,
+        height: u32,
+        vsync: bool,
+        offscreen: bool,
+    ) -> Result<ScreenContextManager, InitError> {
+        let mut canvas_builder = window.into_canvas();
+        canvas_builder = if offscreen {
+            canvas_builder.software()
+        } else {
+            canvas_builder.accelerated()
+        };
+        if vsync {
+            canvas_builder = canvas_builder.present_vsync();
+        }
+        let canvas = canvas_builder.build()?;
 
-        let texture_creator = canvas.texture_creator();
-        let event_pump = sdl.event_pump()?;
+        let window_id = canvas.window().id();
 
         Ok(ScreenContextManager {
-            canvas,
+            output: Output::new(canvas),
+            backend: None,
             // Create empty framebuffer
-            framebuffer: vec![Pixel { r: 0, g: 0, b: 0 }; (width * height) as usize],
-            texture_creator,
-            event_pump,
-            color: Pixel { r: 0, g: 0, b: 0 },
+            framebuffer: vec![Color { r: 0, g: 0, b: 0 }; (width * height) as usize],
+            front_buffer: None,
+            hold_frames: false,
+            hold_spinner_after: None,
+            clip_rect: None,
+            camera: Camera::default(),
+            origin: Origin::default(),
+            color_space: ColorSpace::default(),
+            depth_buffer: None,
+            events: context.register(window_id),
+            window_id,
+            pending_events: VecDeque::new(),
+            scroll_delta: (0, 0),
+            ignore_key_repeats: false,
+            event_handlers: Vec::new(),
+            ready_events: VecDeque::new(),
+            coalesce_motion: false,
+            event_filter: None,
+            post_process: None,
+            dithering: None,
+            controllers: None,
+            joysticks: None,
+            idle_mode: IdleMode::Uncapped,
+            timers: Timers::default(),
+            recent_events: None,
+            crash_reporter: None,
+            frame_index: 0,
+            pacer: None,
+            frame_timer: FrameTimer::default(),
+            show_fps: false,
+            console: Console::default(),
+            software_cursor: None,
+            cursor_image: None,
+            sharpening: None,
+            downsampling: Downsampling::LinearLight,
+            staging: Vec::new(),
+            batch_spans: Vec::new(),
+            batch_pixels: Vec::new(),
+            draw_queue: None,
+            render_targets: Vec::new(),
+            raster_mode: RasterMode::FloatingPoint,
+            alt_enter_fullscreen: None,
+            animation_time: Duration::ZERO,
+            audio_tap: None,
+            speaker: None,
+            audio_samples: Vec::new(),
+            audio_spectrum: Vec::new(),
+            audio_triggers: AudioTriggers::default(),
+            audio_events: VecDeque::new(),
+            pending_captures: Vec::new(),
+            captures: Vec::new(),
+            recording: None,
+            color: Color { r: 0, g: 0, b: 0 },
             height,
             width,
-            width_times_color: width * COLOR_DEPTH,
         })
     }
 
@@ -73,90 +376,532 @@ impl ScreenContextManager {
         self.height
     }
 
+    /// Returns the amount of frames presented so far.
+    pub fn get_frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
+    /// Returns timing statistics over the last couple of seconds of presented frames.
+    pub fn get_frame_stats(&self) -> FrameStats {
+        self.frame_timer.stats()
+    }
+
+    /// Returns when the last present returned, which is after the frame was handed to the display
+    /// and, with vsync enabled, after the vertical blank it was waiting for. Comparing it to the
+    /// timestamp of an input event gives the input-to-photon latency, minus the display's own lag.
+    pub fn get_last_present_timestamp(&self) -> Option<Instant> {
+        self.output.last_present
+    }
+
     /// Sets the color to be used for drawing operations.
-    /// Parameters correspond to RGB colors and must be real numbers in the range [0, 1].
+    /// Parameters correspond to RGB colors and must be real numbers in the range [0, 1], in the
+    /// [color space](ScreenContextManager::set_color_space) set.
     pub fn set_color(&mut self, r: f32, g: f32, b: f32) {
-        self.color = Pixel {
-            r: (r * 255.0).round() as u8,
-            g: (g * 255.0).round() as u8,
-            b: (b * 255.0).round() as u8,
-        };
+        self.color = self.color_space.color(r, g, b);
+        if let Some(backend) = self.backend.as_mut() {
+            backend.set_color(self.color);
+        }
     }
 
-    /// Plots a single pixel on the framebuffer.
+    /// Plots a single pixel on the framebuffer. Pixels outside the framebuffer or the clip
+    /// rectangle are silently ignored, like the parts of every other shape that fall outside.
+    /// To place a point between pixels, e.g. for smooth motion, use
+    /// [`plot_pixel_f`](Self::plot_pixel_f) instead.
     pub fn plot_pixel(&mut self, x: u32, y: u32) {
-        let i = (y * self.width + x) as usize;
-        //println!("Drawing to {}, {}, {}", i, i + 1, i + 2);
+        if self.camera.is_identity() {
+            if let Some((x, y)) = self.locate_pixel(x, y) {
+                self.set_pixel(x, y);
+            }
+            return;
+        }
+        for (x, y) in self.camera_pixels(x, y) {
+            self.set_pixel(x, y);
+        }
+    }
+
+    /// Returns the visible framebuffer pixels that the pixel at drawing coordinates (`x`, `y`)
+    /// covers through the camera.
+    pub(crate) fn camera_pixels(&self, x: u32, y: u32) -> Vec<(u32, u32)> {
+        let (left, top, width, height) = self.camera.pixel_rect(x as i64, y as i64, 1, 1);
+        // Nothing outside the framebuffer can be visible, whatever the origin
+        let columns = left.max(0)..(left + width as i64).min(self.width as i64);
+        let rows = top.max(0)..(top + height as i64).min(self.height as i64);
+        rows.flat_map(|y| columns.clone().map(move |x| (x as u32, y as u32)))
+            .filter_map(|(x, y)| self.locate_pixel(x, y))
+            .collect()
+    }
+
+    /// Maps the drawing coordinates of a pixel to the framebuffer, `None` if it is clipped.
+    pub(crate) fn locate_pixel(&self, x: u32, y: u32) -> Option<(u32, u32)> {
+        let y = match self.origin {
+            Origin::TopLeft => y,
+            Origin::BottomLeft if y < self.height => self.height - 1 - y,
+            Origin::BottomLeft => return None,
+        };
+        self.clip_bounds()
+            .contains(x as i64, y as i64)
+            .then_some((x, y))
+    }
+
+    /// Sets the pixel at framebuffer coordinates (`x`, `y`) to the current color.
+    pub(crate) fn set_pixel(&mut self, x: u32, y: u32) {
+        let i = y as usize * self.width as usize + x as usize;
         self.framebuffer[i] = self.color;
+        if let Some(backend) = self.backend.as_mut() {
+            backend.plot_pixel(x, y);
+        }
+    }
+
+    /// Plots a point at fractional coordinates, splitting the current color between the four
+    /// pixels around it according to how close the point is to each of them (bilinear splatting).
+    /// Integer coordinates hit exactly one pixel, like [`plot_pixel`](Self::plot_pixel). Parts of
+    /// the point outside the framebuffer are dropped.
+    pub fn plot_point_f(&mut self, x: f32, y: f32) {
+        self.report(|| DrawCall::PlotPoint(x, y));
+        let (x, y) = self.world_to_screen(x, y);
+        if self.raster_mode == RasterMode::FixedPoint {
+            return self.plot_point_fixed(x, y);
+        }
+        let (left, top) = (x.floor(), y.floor());
+        let (fx, fy) = (x - left, y - top);
+
+        let corners = [
+            (0, 0, (1.0 - fx) * (1.0 - fy)),
+            (1, 0, fx * (1.0 - fy)),
+            (0, 1, (1.0 - fx) * fy),
+            (1, 1, fx * fy),
+        ];
+        for (dx, dy, weight) in corners {
+            self.blend_pixel(left as i64 + dx, top as i64 + dy, weight);
+        }
+    }
+
+    /// Plots a pixel at fractional coordinates, spreading the current color over the up to four
+    /// pixels it covers, weighted by coverage. Same as [`plot_point_f`](Self::plot_point_f).
+    pub fn plot_pixel_f(&mut self, x: f32, y: f32) {
+        self.plot_point_f(x, y);
+    }
+
+    /// Mixes the current color into the pixel at (`x`, `y`) by `alpha` in the range [0, 1],
+    /// ignoring pixels outside the clip bounds.
+    fn blend_pixel(&mut self, x: i64, y: i64, alpha: f32) {
+        if alpha <= 0.0 || !self.clip_bounds().contains(x, y) {
+            return;
+        }
+        let i = (y * self.width as i64 + x) as usize;
+        self.framebuffer[i] =
+            self.color_space
+                .blend(self.framebuffer[i], self.color, alpha.min(1.0));
     }
 
     /// Clears the entire framebuffer with a grey shadow given by a real number in the range [0,
     /// 1].
     pub fn clear(&mut self, shadow: f32) {
-        let shadow = Pixel {
-            r: (shadow * 255.0).round() as u8,
-            g: (shadow * 255.0).round() as u8,
-            b: (shadow * 255.0).round() as u8,
-        };
+        let shadow = self.color_space.color(shadow, shadow, shadow);
         self.framebuffer.fill(shadow);
+        if let Some(backend) = self.backend.as_mut() {
+            backend.clear(shadow);
+        }
     }
 
     /// Clears the entire framebuffer with the given color.
     /// Parameters correspond to RGB colors and must be real numbers in the range [0, 1].
     pub fn clear_with_rgb(&mut self, r: f32, g: f32, b: f32) {
-        let color = Pixel {
-            r: (r * 255.0).round() as u8,
-            g: (g * 255.0).round() as u8,
-            b: (b * 255.0).round() as u8,
-        };
+        let color = self.color_space.color(r, g, b);
 
         self.framebuffer.fill(color);
+        if let Some(backend) = self.backend.as_mut() {
+            backend.clear(color);
+        }
     }
 
     /// Presents the current contents of the framebuffer on the window's canvas (async)
     pub async fn present_async(&mut self) -> Result<(), PresentationError> {
-        let mut texture = self.texture_creator.create_texture_streaming(
-            PixelFormatEnum::RGB24,
+        self.present()
+    }
+
+    /// Presents the current contents of the framebuffer on the window's canvas
+    ///
+    /// When double buffering is enabled the front buffer is presented instead, see
+    /// [`swap_buffers`](ScreenContextManager::swap_buffers). Commands queued through a
+    /// [`DrawQueue`] run first, before anything is presented.
+    pub fn present(&mut self) -> Result<(), PresentationError> {
+        self.run_draw_queue();
+        self.present_frame()
+    }
+
+    /// Presents the frame like [`present`](ScreenContextManager::present), without running the
+    /// draw queue.
+    fn present_frame(&mut self) -> Result<(), PresentationError> {
+        self.finish_frame();
+        let pixels = self.front_buffer.as_ref().unwrap_or(&self.framebuffer);
+
+        if !self.show_fps
+            && !self.console.is_visible()
+            && self.software_cursor.is_none()
+            && self.sharpening.is_none()
+            && self.post_process.is_none()
+            && self.dithering.is_none()
+        {
+            if let Some(recording) = self.recording.as_mut() {
+                recording.push(pixels, self.width, self.height);
+            }
+            let backend = backend::select(&mut self.output, &mut self.backend);
+            return backend.present(pixels, self.width, self.height, None);
+        }
+
+        // Filters and overlays go on a copy, so that they never end up in the framebuffer itself
+        let mut staging = std::mem::take(&mut self.staging);
+        match self.sharpening {
+            Some(strength) => {
+                staging.resize(pixels.len(), Color::default());
+                sharpen::sharpen(pixels, &mut staging, self.width, self.height, strength);
+            }
+            None => {
+                staging.clear();
+                staging.extend_from_slice(pixels);
+            }
+        }
+        if let Some(post_process) = self.post_process.as_mut() {
+            post_process(
+                bytemuck::cast_slice_mut(&mut staging),
+                self.width,
+                self.height,
+            );
+        }
+        if let Some(dithering) = self.dithering {
+            dither::dither(&mut staging, self.width, self.height, dithering);
+        }
+        self.console.draw(&mut staging, self.width, self.height);
+        if self.show_fps {
+            let fps = self.frame_timer.stats().fps;
+            overlay::draw_fps(&mut staging, self.width, self.height, fps);
+        }
+        self.draw_software_cursor(&mut staging);
+        if let Some(recording) = self.recording.as_mut() {
+            recording.push(&staging, self.width, self.height);
+        }
+        self.staging = staging;
+        backend::select(&mut self.output, &mut self.backend).present(
+            &self.staging,
             self.width,
             self.height,
-        )?;
-
-        texture.update(
             None,
-            bytemuck::cast_slice(&self.framebuffer),
-            (self.width_times_color) as usize,
-        )?;
+        )
+    }
 
-        self.canvas.copy(&texture, None, None)?;
-        self.canvas.present();
+    /// Shows or hides a frames per second counter in the top-left corner of the window. The
+    /// counter is drawn over the presented frame, the framebuffer itself is left untouched.
+    pub fn show_fps(&mut self, show: bool) {
+        self.show_fps = show;
+    }
 
+    /// Runs `effect` on the raw RGB24 bytes of every presented frame, along with its width and
+    /// height, just before it is uploaded. Meant for effects like vignettes or color grading;
+    /// the framebuffer itself is left untouched, and overlays like the fps counter are drawn
+    /// after it. [`present_rect`](ScreenContextManager::present_rect) skips the effect.
+    pub fn set_post_process<F>(&mut self, effect: F)
+    where
+        F: FnMut(&mut [u8], u32, u32) + 'static,
+    {
+        self.post_process = Some(Box::new(effect));
+    }
+
+    /// Removes the effect set with [`set_post_process`](ScreenContextManager::set_post_process).
+    pub fn clear_post_process(&mut self) {
+        self.post_process = None;
+    }
+
+    /// Presents the framebuffer, then waits out the rest of the frame so that the target frame
+    /// rate set with [`set_target_fps`](ScreenContextManager::set_target_fps) is held.
+    pub fn present_and_pace(&mut self) -> Result<(), PresentationError> {
+        self.present()?;
+        if let Some(pacer) = self.pacer.as_mut() {
+            pacer.wait();
+        }
         Ok(())
     }
 
-    /// Presents the current contents of the framebuffer on the window's canvas
-    pub fn present(&mut self) -> Result<(), PresentationError> {
-        let mut texture = self.texture_creator.create_texture_streaming(
-            PixelFormatEnum::RGB24,
+    /// Sets the frame rate that [`present_and_pace`](ScreenContextManager::present_and_pace)
+    /// holds. A target of 0 disables pacing.
+    pub fn set_target_fps(&mut self, fps: u32) {
+        self.pacer = if fps > 0 {
+            Some(FramePacer::new(fps))
+        } else {
+            None
+        };
+    }
+
+    /// Presents the framebuffer like [`present`](ScreenContextManager::present), but only uploads
+    /// the pixels inside the given rectangle. The rest of the window keeps showing what was last
+    /// uploaded there, so this is only useful when nothing outside the rectangle has changed.
+    /// Commands queued through a [`DrawQueue`] run first, before anything is presented.
+    pub fn present_rect(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), PresentationError> {
+        self.run_draw_queue();
+        self.present_region(x, y, width, height)
+    }
+
+    /// Presents part of the frame like [`present_rect`](ScreenContextManager::present_rect),
+    /// without running the draw queue.
+    fn present_region(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), PresentationError> {
+        let x = x.min(self.width);
+        let y = y.min(self.height);
+        let width = width.min(self.width - x);
+        let height = height.min(self.height - y);
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        self.finish_frame();
+        let region = (x, y, width, height);
+        let pixels = self.front_buffer.as_ref().unwrap_or(&self.framebuffer);
+        if let Some(recording) = self.recording.as_mut() {
+            recording.push(pixels, self.width, self.height);
+        }
+        backend::select(&mut self.output, &mut self.backend).present(
+            pixels,
             self.width,
             self.height,
-        )?;
+            Some(region),
+        )
+    }
 
-        texture.update(
-            None,
-            bytemuck::cast_slice(&self.framebuffer),
-            (self.width_times_color) as usize,
-        )?;
+    /// Bookkeeping for a frame that is about to be presented.
+    fn finish_frame(&mut self) {
+        self.frame_index += 1;
+        self.frame_timer.record();
+        self.captures = std::mem::take(&mut self.pending_captures);
+        self.snapshot_audio();
+    }
 
-        self.canvas.copy(&texture, None, None)?;
-        self.canvas.present();
+    /// Presents the front buffer again without it counting as a new frame, with the progress
+    /// spinner for the given wait drawn over it if `spinner` is set. The buffer itself is left
+    /// untouched.
+    fn present_held(&mut self, spinner: Option<Duration>) -> Result<(), PresentationError> {
+        let pixels = self.front_buffer.as_ref().unwrap_or(&self.framebuffer);
+        let backend = backend::select(&mut self.output, &mut self.backend);
+        match spinner {
+            Some(elapsed) => {
+                let mut pixels = pixels.clone();
+                overlay::draw_spinner(&mut pixels, self.width, self.height, elapsed);
+                backend.present(&pixels, self.width, self.height, None)
+            }
+            None => backend.present(pixels, self.width, self.height, None),
+        }
+    }
 
-        Ok(())
+    /// Time between two refreshes of the display the window is on, falling back to 60Hz when SDL
+    /// doesn't know the refresh rate.
+    fn refresh_interval(&self) -> Duration {
+        let rate = match self.output.canvas.window().display_mode() {
+            Ok(mode) if mode.refresh_rate > 0 => mode.refresh_rate as u32,
+            _ => 60,
+        };
+        Duration::from_secs(1) / rate
+    }
+
+    /// Enables or disables double buffering. While enabled, drawing operations target a back
+    /// buffer and [`present`](ScreenContextManager::present) shows the front buffer, so the two
+    /// only meet when [`swap_buffers`](ScreenContextManager::swap_buffers) is called. While a
+    /// [render target](ScreenContextManager::push_render_target) is pushed, this only applies
+    /// to that target.
+    pub fn set_double_buffering(&mut self, enabled: bool) {
+        if enabled {
+            if self.front_buffer.is_none() {
+                self.front_buffer = Some(self.framebuffer.clone());
+            }
+        } else {
+            self.front_buffer = None;
+        }
+    }
+
+    /// Exchanges the back buffer (the one being drawn) with the front buffer (the one being
+    /// presented). Does nothing when double buffering is disabled.
+    pub fn swap_buffers(&mut self) {
+        if let Some(front) = self.front_buffer.as_mut() {
+            std::mem::swap(front, &mut self.framebuffer);
+        }
+    }
+
+    /// Returns the raw RGB24 bytes of the buffer that drawing operations write to.
+    pub fn back_buffer_mut(&mut self) -> &mut [u8] {
+        bytemuck::cast_slice_mut(&mut self.framebuffer)
+    }
+
+    /// Presents the front buffer while `draw` fills the back buffer on another thread, returning
+    /// whatever `draw` returns. Without double buffering the two steps run one after the other.
+    /// Commands queued through a [`DrawQueue`] run before both, not during them.
+    pub fn present_while<F, R>(&mut self, draw: F) -> Result<R, PresentationError>
+    where
+        F: FnOnce(&mut [u8]) -> R + Send,
+        R: Send,
+    {
+        // The queued commands draw into the back buffer, so they can't run once it is taken
+        self.run_draw_queue();
+        if self.front_buffer.is_none() {
+            self.present_frame()?;
+            return Ok(draw(self.back_buffer_mut()));
+        }
+
+        let mut back = std::mem::take(&mut self.framebuffer);
+        let (presented, drawn) = std::thread::scope(|scope| {
+            let drawing = scope.spawn(|| draw(bytemuck::cast_slice_mut(&mut back)));
+            let mut presented = self.present_frame();
+
+            if self.hold_frames {
+                let started = Instant::now();
+                let interval = self.refresh_interval();
+                while presented.is_ok() && !drawing.is_finished() {
+                    std::thread::sleep(interval);
+                    self.events.borrow_mut().pump();
+
+                    let elapsed = started.elapsed();
+                    let spinner = match self.hold_spinner_after {
+                        Some(after) if elapsed >= after => Some(elapsed),
+                        _ => None,
+                    };
+                    presented = self.present_held(spinner);
+                }
+            }
+
+            (presented, drawing.join())
+        });
+        self.framebuffer = back;
+
+        presented?;
+        match drawn {
+            Ok(value) => Ok(value),
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+
+    /// Enables or disables frame holding for [`present_while`](ScreenContextManager::present_while).
+    /// While holding, the front buffer is re-presented at the display's refresh rate until the
+    /// slow `draw` finishes, keeping the window responsive. If `spinner_after` is given, a small
+    /// progress spinner is shown over the held frame once the wait exceeds that duration.
+    pub fn set_frame_hold(&mut self, enabled: bool, spinner_after: Option<Duration>) {
+        self.hold_frames = enabled;
+        self.hold_spinner_after = spinner_after;
+    }
+
+    /// Resizes the framebuffer to a logical resolution independent of the window size, clearing
+    /// it to black. Pair it with [`ScaleMode::Integer`] to present e.g. a 320×240 framebuffer
+    /// scaled up on a much bigger window.
+    pub fn set_logical_size(&mut self, width: u32, height: u32) {
+        let black = Color { r: 0, g: 0, b: 0 };
+        self.width = width;
+        self.height = height;
+        self.framebuffer = vec![black; (width * height) as usize];
+        if let Some(front) = self.front_buffer.as_mut() {
+            *front = self.framebuffer.clone();
+        }
+        if let Some(depth) = self.depth_buffer.as_mut() {
+            *depth = vec![f32::INFINITY; self.framebuffer.len()];
+        }
+    }
+
+    /// Sets how the framebuffer is fitted onto the window when presenting.
+    pub fn set_scale_mode(&mut self, mode: ScaleMode) {
+        self.output.scale_mode = mode;
+    }
+
+    /// Sets the color the window shows around the framebuffer when it doesn't cover the whole
+    /// window, e.g. the letterbox bars. Parameters correspond to RGB colors and must be real
+    /// numbers in the range [0, 1].
+    pub fn set_background_color(&mut self, r: f32, g: f32, b: f32) {
+        let color = self.color_space.color(r, g, b);
+        self.output.background = sdl2::pixels::Color::RGB(color.r, color.g, color.b);
+    }
+
+    /// Sets the filter used when the framebuffer is scaled to fit the window. Defaults to
+    /// [`ScaleFilter::Nearest`].
+    pub fn set_scale_filter(&mut self, filter: ScaleFilter) {
+        self.output.set_scale_filter(filter);
     }
 
     /// Returns an iterator that will hold all the current window events. The iterator will
     /// terminate once there are no pending events.
-    pub fn get_events(&mut self) -> EventPollIterator {
-        self.event_pump.poll_iter()
+    pub fn get_events(&mut self) -> impl Iterator<Item = ScreenEvent> + '_ {
+        self.get_timed_events().map(|timed| timed.event)
+    }
+
+    /// Like [`get_events`](ScreenContextManager::get_events), but with every event's timestamp and
+    /// the index of the frame it was read in, so that input can be correlated with frames.
+    pub fn get_timed_events(&mut self) -> impl Iterator<Item = TimedEvent> + '_ {
+        self.events
+            .borrow_mut()
+            .take(self.window_id, &mut self.pending_events);
+        if self.coalesce_motion {
+            events::coalesce_motion(&mut self.pending_events);
+        }
+        let recent = &mut self.recent_events;
+        let output = &mut self.output;
+        let (width, height) = (self.width, self.height);
+        let alt_enter_fullscreen = self.alt_enter_fullscreen;
+        let console = &mut self.console;
+        let ignore_key_repeats = self.ignore_key_repeats;
+        let controllers = &mut self.controllers;
+        let joysticks = &mut self.joysticks;
+        let sdl_events = self
+            .pending_events
+            .drain(..)
+            .inspect(move |event| crash::record_event(recent, event))
+            .filter_map(move |event| {
+                let sdl_window = output.canvas.window_mut();
+                if window::handle_alt_enter(sdl_window, alt_enter_fullscreen, &event)
+                    || console.handle(&event)
+                    || ignore_key_repeats && matches!(event, Event::KeyDown { repeat: true, .. })
+                {
+                    return None;
+                }
+                if let Some(joysticks) = joysticks.as_mut() {
+                    joysticks.handle(&event);
+                }
+
+                let timestamp = Duration::from_millis(event.get_timestamp() as u64);
+                let unhandled = match controllers.as_mut() {
+                    Some(controllers) => controllers.handle(event),
+                    None => Err(event),
+                };
+                let event = match unhandled {
+                    Ok(event) => event,
+                    Err(event) => events::translate(event, |x, y| {
+                        output.window_to_framebuffer(x, y, width, height)
+                    })?,
+                };
+                Some((timestamp, event))
+            });
+        let scroll_delta = &mut self.scroll_delta;
+        let frame_index = self.frame_index;
+        let event_filter = &mut self.event_filter;
+        let wrapper_events = self.audio_events.drain(..).chain(sdl_events);
+        let wrapper_events = wrapper_events
+            .filter(move |(_, event)| event_filter.as_mut().is_none_or(|keep| keep(event)))
+            .map(move |(timestamp, event)| {
+                if let ScreenEvent::Scroll { x, y } = event {
+                    scroll_delta.0 += x;
+                    scroll_delta.1 += y;
+                }
+                TimedEvent {
+                    event,
+                    timestamp,
+                    frame_index,
+                }
+            });
+        // Events read ahead while waiting come first
+        self.ready_events.drain(..).chain(wrapper_events)
     }
 
     /// Saves the current framebuffer as an image whose format is derived from the file extension.
@@ -170,6 +915,82 @@ impl ScreenContextManager {
             image::ColorType::Rgb8,
         )?)
     }
+
+    /// Like [`save_img`](ScreenContextManager::save_img), but saves an alpha channel in which the
+    /// pixels of the `background` color are fully transparent and all others opaque. The format
+    /// must support transparency, like PNG.
+    pub fn save_img_transparent<P: AsRef<Path>>(
+        &self,
+        path: P,
+        background: Color,
+    ) -> Result<(), SaveImageError> {
+        let buffer: Vec<u8> = self
+            .framebuffer
+            .iter()
+            .flat_map(|&pixel| {
+                let alpha = if pixel == background { 0 } else { 255 };
+                [pixel.r, pixel.g, pixel.b, alpha]
+            })
+            .collect();
+        Ok(image::save_buffer(
+            path,
+            &buffer,
+            self.width,
+            self.height,
+            image::ColorType::Rgba8,
+        )?)
+    }
+
+    /// Saves the current framebuffer as a binary (P6) PPM image, written directly without going
+    /// through the `image` crate.
+    pub fn save_ppm<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveImageError> {
+        let mut file = BufWriter::new(File::create(path)?);
+        write!(file, "P6\n{} {}\n255\n", self.width, self.height)?;
+        file.write_all(bytemuck::cast_slice(&self.framebuffer))?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for ScreenContextManager {
+    fn drop(&mut self) {
+        self.events.borrow_mut().unregister(self.window_id);
+        // Complete the file rather than losing the capture when the screen goes away first, e.g.
+        // by returning early with `?`. There's no one left to report an error to, so it's dropped;
+        // stop_recording is there to see it.
+        if let Some(recording) = self.recording.take() {
+            let _ = recording.finish();
+        }
+    }
+}
+
+/// Any error of this crate, for applications that would rather use a single error type.
+#[derive(Error, Debug)]
+pub enum ScreenError {
+    #[error("{0}")]
+    Init(#[from] InitError),
+    #[error("{0}")]
+    Presentation(#[from] PresentationError),
+    #[error("{0}")]
+    SaveImage(#[from] SaveImageError),
+    #[error("{0}")]
+    LoadImage(#[from] LoadImageError),
+    #[error("{0}")]
+    Window(#[from] WindowError),
+    #[error("{0}")]
+    Audio(#[from] AudioError),
+    #[error("{0}")]
+    Recording(#[from] RecordingError),
+    #[error("{0}")]
+    Golden(#[from] GoldenError),
+    #[error("{0}")]
+    CrashDump(#[from] CrashDumpError),
+    #[cfg(feature = "ttf")]
+    #[error("{0}")]
+    Font(#[from] FontError),
+    #[cfg(feature = "svg")]
+    #[error("{0}")]
+    SvgPath(#[from] SvgPathError),
 }
 
 #[derive(Error, Debug)]
@@ -206,8 +1027,118 @@ impl From<String> for PresentationError {
     }
 }
 
+#[derive(Error, Debug)]
+pub enum WindowError {
+    #[error("{0}")]
+    Sdl(String),
+    #[error("text passed to SDL can't contain NUL characters")]
+    Nul(#[from] std::ffi::NulError),
+    #[error("{0}")]
+    Image(#[from] image::error::ImageError),
+}
+
+impl From<String> for WindowError {
+    fn from(msg: String) -> Self {
+        WindowError::Sdl(msg)
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum SaveImageError {
     #[error("{0}")]
     SaveBMP(#[from] image::error::ImageError),
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum AudioError {
+    #[error("{0}")]
+    Sdl(String),
+}
+
+impl From<String> for AudioError {
+    fn from(msg: String) -> Self {
+        AudioError::Sdl(msg)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum LoadImageError {
+    #[error("{0}")]
+    Decode(#[from] image::error::ImageError),
+}
+
+#[derive(Error, Debug)]
+pub enum GoldenError {
+    #[error("{0}")]
+    Init(#[from] InitError),
+    #[error("{0}")]
+    Image(#[from] image::error::ImageError),
+    #[error("{0}")]
+    Save(#[from] SaveImageError),
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("missing golden images (run with SDL_WRAPPER_BLESS=1 to create them): {}", .0.join(", "))]
+    Missing(Vec<String>),
+    #[error("rendered images differ from their goldens: {}", .0.join(", "))]
+    Mismatch(Vec<String>),
+}
+
+#[derive(Error, Debug)]
+pub enum CrashDumpError {
+    #[error("failed to save the crash frame: {0}")]
+    SaveFrame(#[from] SaveImageError),
+    #[error("failed to write the crash report: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(feature = "svg")]
+#[derive(Error, Debug)]
+pub enum SvgPathError {
+    #[error("unexpected character '{0}' in path data")]
+    UnexpectedCharacter(char),
+    #[error("unexpected path command '{0}'")]
+    UnexpectedCommand(char),
+    #[error("path command '{0}' is missing numbers")]
+    MissingNumbers(char),
+}
+
+#[cfg(feature = "ttf")]
+#[derive(Error, Debug)]
+pub enum FontError {
+    #[error("{0}")]
+    Sdl(String),
+}
+
+#[cfg(feature = "ttf")]
+impl From<String> for FontError {
+    fn from(msg: String) -> Self {
+        FontError::Sdl(msg)
+    }
+}
+
+#[cfg(feature = "ttf")]
+impl From<&str> for FontError {
+    fn from(msg: &str) -> Self {
+        FontError::Sdl(msg.to_string())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum RecordingError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Encode(#[from] image::error::ImageError),
+    #[cfg(feature = "apng")]
+    #[error("{0}")]
+    EncodePng(#[from] png::EncodingError),
+    #[error("the framebuffer changed size while recording")]
+    FrameSize,
+    #[error("no frames were presented while recording")]
+    NoFrames,
+    #[cfg(feature = "ffmpeg")]
+    #[error("ffmpeg failed with {0}")]
+    Ffmpeg(std::process::ExitStatus),
 }
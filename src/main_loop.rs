@@ -0,0 +1,92 @@
+//! This module provides a main loop helper so applications don't have to write the same
+//! draw/present/wait loop over and over.
+
+use crate::{timing::FramePacer, PresentationError, ScreenContextManager};
+use std::time::Instant;
+
+/// How the loop helper waits between frames.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IdleMode {
+    /// Only draw a new frame once there is at least one pending event or a timer is due,
+    /// sleeping otherwise. Meant for GUI-like tools that only change on input. Events the frame
+    /// doesn't read are dropped once it is presented, so that they don't keep the loop awake.
+    WaitForEvents,
+    /// Draw at most the given amount of frames per second, 0 meaning no limit like
+    /// [`Uncapped`](IdleMode::Uncapped).
+    Fps(u32),
+    /// Draw frames as fast as possible.
+    Uncapped,
+}
+
+/// What the loop helper should do after a frame has been drawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LoopAction {
+    /// Present the frame and keep looping.
+    Continue,
+    /// Present the frame and return from the loop.
+    Exit,
+}
+
+impl ScreenContextManager {
+    /// Sets how [`run`](ScreenContextManager::run) waits between frames.
+    pub fn set_idle_mode(&mut self, mode: IdleMode) {
+        self.idle_mode = mode;
+    }
+
+    /// Runs a main loop that calls `frame` to draw every frame and presents it afterwards, pacing
+    /// itself according to the current [`IdleMode`]. Returns once `frame` asks to exit.
+    pub fn run<F>(&mut self, mut frame: F) -> Result<(), PresentationError>
+    where
+        F: FnMut(&mut ScreenContextManager) -> LoopAction,
+    {
+        let mut pacer: Option<FramePacer> = None;
+        let mut first = true;
+        let mut last_frame = Instant::now();
+
+        loop {
+            if self.idle_mode == IdleMode::WaitForEvents
+                && !first
+                && self.pending_events.is_empty()
+                && self.audio_events.is_empty()
+                && self.ready_events.is_empty()
+            {
+                // Timers wake the loop up too
+                let timeout = self
+                    .timers
+                    .next_deadline()
+                    .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+                let mut events = self.events.borrow_mut();
+                events.wait(self.window_id, timeout);
+                events.take(self.window_id, &mut self.pending_events);
+            }
+            first = false;
+            if !self.event_handlers.is_empty() {
+                self.pump_events();
+            }
+            self.run_timers();
+
+            let now = Instant::now();
+            self.advance_animation_clock(now - last_frame);
+            last_frame = now;
+
+            let action = frame(self);
+            self.present()?;
+            if action == LoopAction::Exit {
+                return Ok(());
+            }
+
+            match self.idle_mode {
+                IdleMode::WaitForEvents => {
+                    self.pending_events.clear();
+                    self.audio_events.clear();
+                    self.ready_events.clear();
+                }
+                IdleMode::Fps(fps) if fps > 0 => match pacer {
+                    Some(ref mut pacer) if pacer.fps() == fps => pacer.wait(),
+                    _ => pacer.insert(FramePacer::new(fps)).wait(),
+                },
+                IdleMode::Fps(_) | IdleMode::Uncapped => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! This module exposes the native handles of the window, for the occasional integration with
+//! graphics APIs or platform dialogs that the framebuffer can't cover.
+
+use crate::{ScreenContextManager, WindowError};
+#[cfg(feature = "raw")]
+use sdl2::render::Canvas;
+use sdl2::sys;
+use sdl2::video::Window;
+use std::ffi::c_void;
+use std::mem::{size_of, MaybeUninit};
+
+/// The native handles of a window, depending on the windowing system it lives in. The handles
+/// stay valid for as long as the screen is around.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NativeWindow {
+    /// A window on Windows.
+    Win32 {
+        hwnd: *mut c_void,
+        hinstance: *mut c_void,
+    },
+    /// An X11 window and the connection to its display.
+    Xlib { display: *mut c_void, window: u64 },
+    /// A Wayland surface and the connection to its display.
+    Wayland {
+        display: *mut c_void,
+        surface: *mut c_void,
+    },
+    /// An `NSWindow*` on macOS.
+    AppKit { ns_window: *mut c_void },
+    /// Any other windowing system, identified by SDL's `SDL_SYSWM_TYPE` value.
+    Other(u32),
+}
+
+impl ScreenContextManager {
+    /// Returns the SDL window the framebuffer is presented on, for SDL functionality this crate
+    /// doesn't cover. For the platform's own handles, see
+    /// [`get_native_window`](ScreenContextManager::get_native_window).
+    pub fn get_sdl_window(&self) -> &Window {
+        self.output.canvas.window()
+    }
+
+    /// Runs `f` on the SDL canvas the framebuffer is presented on, for SDL functionality this
+    /// crate doesn't cover. Whatever is drawn on the canvas is covered by the framebuffer on the
+    /// next present, and changes to the canvas' state, like its render target or logical size,
+    /// are left for presenting to cope with, so they must be undone before returning.
+    #[cfg(feature = "raw")]
+    pub fn with_canvas<R>(&mut self, f: impl FnOnce(&mut Canvas<Window>) -> R) -> R {
+        f(&mut self.output.canvas)
+    }
+
+    /// Returns the native handles of the window, to create a graphics API surface for it or
+    /// parent a native dialog to it.
+    pub fn get_native_window(&self) -> Result<NativeWindow, WindowError> {
+        let mut info = MaybeUninit::<sys::SDL_SysWMinfo>::zeroed();
+        // SAFETY: `info` is zeroed, so every byte read from it is initialized, and it outlives
+        // the call. The window belongs to this screen's canvas and is alive. `subsystem` is a C
+        // enum, which is as large as a u32, and only as many words as fit into the union are
+        // read, unaligned since the union's alignment isn't guaranteed to be a usize's.
+        let (subsystem, words) = unsafe {
+            let version = std::ptr::addr_of_mut!((*info.as_mut_ptr()).version);
+            *version = sys::SDL_version {
+                major: sys::SDL_MAJOR_VERSION as u8,
+                minor: sys::SDL_MINOR_VERSION as u8,
+                patch: sys::SDL_PATCHLEVEL as u8,
+            };
+            let raw = self.output.canvas.window().raw();
+            if sys::SDL_GetWindowWMInfo(raw, info.as_mut_ptr()) != sys::SDL_bool::SDL_TRUE {
+                return Err(sdl2::get_error().into());
+            }
+            // Read as a plain number, since newer SDL versions report subsystems the bindings
+            // don't know about
+            let subsystem = *(std::ptr::addr_of!((*info.as_ptr()).subsystem) as *const u32);
+            // Every platform's handles are pointer-sized words at the start of the union
+            let union = std::ptr::addr_of!((*info.as_ptr()).info) as *const usize;
+            let mut words = [0usize; 3];
+            let count = words
+                .len()
+                .min(size_of::<sys::SDL_SysWMinfo__bindgen_ty_1>() / size_of::<usize>());
+            for (i, word) in words.iter_mut().enumerate().take(count) {
+                *word = union.add(i).read_unaligned();
+            }
+            (subsystem, words)
+        };
+
+        use sys::SDL_SYSWM_TYPE::*;
+        let pointer = |word: usize| word as *mut c_void;
+        Ok(match subsystem {
+            x if x == SDL_SYSWM_WINDOWS as u32 => NativeWindow::Win32 {
+                hwnd: pointer(words[0]),
+                hinstance: pointer(words[2]),
+            },
+            x if x == SDL_SYSWM_X11 as u32 => NativeWindow::Xlib {
+                display: pointer(words[0]),
+                window: words[1] as u64,
+            },
+            x if x == SDL_SYSWM_WAYLAND as u32 => NativeWindow::Wayland {
+                display: pointer(words[0]),
+                surface: pointer(words[1]),
+            },
+            x if x == SDL_SYSWM_COCOA as u32 => NativeWindow::AppKit {
+                ns_window: pointer(words[0]),
+            },
+            other => NativeWindow::Other(other),
+        })
+    }
+}
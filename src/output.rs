@@ -0,0 +1,184 @@
+//! This module handles uploading the framebuffer to SDL and getting it on the window.
+
+use crate::{constants::COLOR_DEPTH, Backend, Color, PresentationError};
+use sdl2::{
+    pixels::{Color as SdlColor, PixelFormatEnum},
+    rect::Rect,
+    render::{Canvas, Texture, TextureCreator},
+    video::{Window, WindowContext},
+};
+use std::time::Instant;
+
+/// How the framebuffer is fitted onto the window's canvas when presenting.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Stretch the framebuffer over the whole canvas.
+    Stretch,
+    /// Keep the framebuffer at its native size, centered on the canvas with borders of the
+    /// background color around it.
+    Center,
+    /// Scale the framebuffer up by the largest whole factor that fits the canvas, centered with
+    /// letterbox bars of the background color around it. Every framebuffer pixel becomes a crisp
+    /// square.
+    Integer,
+}
+
+/// Filter used to sample the framebuffer when it is scaled to a different size on the window.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScaleFilter {
+    /// Nearest-neighbor sampling, keeps pixel art crisp.
+    Nearest,
+    /// Linear interpolation, smoother for photos and other continuous images.
+    Linear,
+}
+
+/// Everything SDL needs to get a framebuffer on screen.
+pub struct Output {
+    pub canvas: Canvas<Window>,
+    pub texture_creator: TextureCreator<WindowContext>,
+    /// Streaming texture the framebuffer is uploaded to, kept between frames so that partial
+    /// uploads only need to touch the pixels that changed.
+    pub texture: Option<Texture>,
+    pub scale_mode: ScaleMode,
+    scale_filter: ScaleFilter,
+    /// Color of the canvas around the framebuffer when it doesn't cover all of it.
+    pub background: SdlColor,
+    /// When the canvas last returned from presenting, after waiting for vsync if enabled.
+    pub last_present: Option<Instant>,
+}
+
+impl Output {
+    pub fn new(canvas: Canvas<Window>) -> Output {
+        let texture_creator = canvas.texture_creator();
+        Output {
+            canvas,
+            texture_creator,
+            texture: None,
+            scale_mode: ScaleMode::Stretch,
+            scale_filter: ScaleFilter::Nearest,
+            background: SdlColor::RGB(0, 0, 0),
+            last_present: None,
+        }
+    }
+
+    /// Sets the filter used for scaling, which takes effect with the next uploaded frame.
+    pub fn set_scale_filter(&mut self, filter: ScaleFilter) {
+        if filter != self.scale_filter {
+            self.scale_filter = filter;
+            self.destroy_texture();
+        }
+    }
+
+    /// Gets rid of the streaming texture, so that the next present creates a new one.
+    fn destroy_texture(&mut self) {
+        if let Some(old) = self.texture.take() {
+            // SAFETY: the texture was created by this output's renderer, which is still alive,
+            // and it is not referenced anywhere else.
+            unsafe { old.destroy() };
+        }
+    }
+
+    /// Converts a position in window coordinates to the pixel of a `width`×`height` framebuffer
+    /// shown there, which may lie outside of the framebuffer.
+    pub fn window_to_framebuffer(&self, x: i32, y: i32, width: u32, height: u32) -> (i32, i32) {
+        let (window_width, window_height) = self.canvas.window().size();
+        // The canvas has more pixels than the window on high-DPI displays
+        let (canvas_width, canvas_height) = self
+            .canvas
+            .output_size()
+            .unwrap_or((window_width, window_height));
+        let x = x as f64 * canvas_width as f64 / window_width.max(1) as f64;
+        let y = y as f64 * canvas_height as f64 / window_height.max(1) as f64;
+
+        let destination = self
+            .destination(width, height)
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| Rect::new(0, 0, canvas_width, canvas_height));
+        let x = (x - destination.x() as f64) * width as f64 / destination.width() as f64;
+        let y = (y - destination.y() as f64) * height as f64 / destination.height() as f64;
+        (x.floor() as i32, y.floor() as i32)
+    }
+
+    /// Where on the canvas a `width`×`height` framebuffer goes, `None` meaning all of it.
+    fn destination(&self, width: u32, height: u32) -> Result<Option<Rect>, String> {
+        let scale = match self.scale_mode {
+            ScaleMode::Stretch => return Ok(None),
+            ScaleMode::Center => 1,
+            ScaleMode::Integer => {
+                let (canvas_width, canvas_height) = self.canvas.output_size()?;
+                (canvas_width / width).min(canvas_height / height).max(1)
+            }
+        };
+
+        let (canvas_width, canvas_height) = self.canvas.output_size()?;
+        Ok(Some(Rect::new(
+            (canvas_width as i32 - (width * scale) as i32) / 2,
+            (canvas_height as i32 - (height * scale) as i32) / 2,
+            width * scale,
+            height * scale,
+        )))
+    }
+}
+
+impl Backend for Output {
+    /// Uploads `pixels` (or only the part of them inside `region`) to the streaming texture and
+    /// presents it on the canvas according to the scale mode.
+    fn present(
+        &mut self,
+        pixels: &[Color],
+        width: u32,
+        height: u32,
+        region: Option<(u32, u32, u32, u32)>,
+    ) -> Result<(), PresentationError> {
+        // A texture left over from a different framebuffer size can't be reused, and a fresh
+        // one has to be filled completely
+        let reusable = self.texture.as_ref().is_some_and(|texture| {
+            let query = texture.query();
+            query.width == width && query.height == height
+        });
+        let region = if reusable {
+            region.map(|(x, y, width, height)| Rect::new(x as i32, y as i32, width, height))
+        } else {
+            self.destroy_texture();
+
+            // SDL picks the filter of a texture from this hint when it gets created
+            let quality = match self.scale_filter {
+                ScaleFilter::Nearest => "nearest",
+                ScaleFilter::Linear => "linear",
+            };
+            sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", quality);
+            let texture = self.texture_creator.create_texture_streaming(
+                PixelFormatEnum::RGB24,
+                width,
+                height,
+            )?;
+            self.texture = Some(texture);
+            None
+        };
+
+        let pitch = (width * COLOR_DEPTH) as usize;
+        let bytes: &[u8] = bytemuck::cast_slice(pixels);
+        let texture = self.texture.as_mut().expect("texture was just created");
+        match region {
+            Some(rect) => {
+                let start = (rect.y() as u32 * width + rect.x() as u32) * COLOR_DEPTH;
+                texture.update(rect, &bytes[start as usize..], pitch)?;
+            }
+            None => texture.update(None, bytes, pitch)?,
+        }
+
+        let destination = self.destination(width, height)?;
+        if destination.is_some() {
+            self.canvas.set_draw_color(self.background);
+            self.canvas.clear();
+        }
+        if let Some(texture) = self.texture.as_ref() {
+            self.canvas.copy(texture, None, destination)?;
+        }
+        self.canvas.present();
+        self.last_present = Some(Instant::now());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+//! This module draws the overlays that get composited over the framebuffer at present time,
+//! without ever touching the framebuffer itself.
+
+use crate::Color;
+use std::time::Duration;
+
+/// Amount of dots in the progress spinner
+const SPINNER_DOTS: u32 = 8;
+
+/// Draws a spinner of rotating dots in the bottom-right corner of `pixels`, its phase given by
+/// `elapsed`.
+pub fn draw_spinner(pixels: &mut [Color], width: u32, height: u32, elapsed: Duration) {
+    const RADIUS: f32 = 10.0;
+    const DOT: i32 = 3;
+    const MARGIN: f32 = 24.0;
+
+    let center_x = width as f32 - MARGIN;
+    let center_y = height as f32 - MARGIN;
+    let head = (elapsed.as_millis() / 100) as u32 % SPINNER_DOTS;
+
+    for dot in 0..SPINNER_DOTS {
+        let angle = dot as f32 / SPINNER_DOTS as f32 * std::f32::consts::TAU;
+        let x = (center_x + RADIUS * angle.cos()).round() as i32;
+        let y = (center_y + RADIUS * angle.sin()).round() as i32;
+
+        // Dots fade out the further behind the head they are
+        let age = (head + SPINNER_DOTS - dot) % SPINNER_DOTS;
+        let shade = 255 - (age * 255 / SPINNER_DOTS) as u8;
+        let color = Color {
+            r: shade,
+            g: shade,
+            b: shade,
+        };
+
+        for dy in -DOT / 2..=DOT / 2 {
+            for dx in -DOT / 2..=DOT / 2 {
+                let (px, py) = (x + dx, y + dy);
+                if px >= 0 && py >= 0 && (px as u32) < width && (py as u32) < height {
+                    pixels[(py as u32 * width + px as u32) as usize] = color;
+                }
+            }
+        }
+    }
+}
+
+/// Glyphs of the tiny overlay font, 3 pixels wide and 5 tall. Each row is stored in the low 3
+/// bits of a byte, most significant bit on the left.
+const TINY_GLYPHS: &[(char, [u8; 5])] = &[
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b010, 0b010, 0b010]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('.', [0b000, 0b000, 0b000, 0b000, 0b010]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('P', [0b110, 0b101, 0b110, 0b100, 0b100]),
+    ('S', [0b011, 0b100, 0b010, 0b001, 0b110]),
+    (' ', [0b000, 0b000, 0b000, 0b000, 0b000]),
+];
+
+/// Size of every tiny font pixel on the framebuffer
+const TINY_SCALE: u32 = 2;
+
+/// Fills a rectangle, clipped against the buffer.
+fn fill_rect(
+    pixels: &mut [Color],
+    width: u32,
+    height: u32,
+    rect: (u32, u32, u32, u32),
+    color: Color,
+) {
+    let (x, y, w, h) = rect;
+    for py in y..(y + h).min(height) {
+        for px in x..(x + w).min(width) {
+            pixels[(py * width + px) as usize] = color;
+        }
+    }
+}
+
+/// Draws `text` with the tiny font with its top-left corner at (`x`, `y`). Characters the font
+/// doesn't have are skipped.
+fn draw_tiny_text(pixels: &mut [Color], width: u32, height: u32, x: u32, y: u32, text: &str) {
+    let white = Color {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+
+    for (i, c) in text.chars().enumerate() {
+        let glyph = match TINY_GLYPHS.iter().find(|(glyph, _)| *glyph == c) {
+            Some((_, rows)) => rows,
+            None => continue,
+        };
+        let glyph_x = x + i as u32 * 4 * TINY_SCALE;
+        for (row, bits) in glyph.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) != 0 {
+                    let rect = (
+                        glyph_x + column * TINY_SCALE,
+                        y + row as u32 * TINY_SCALE,
+                        TINY_SCALE,
+                        TINY_SCALE,
+                    );
+                    fill_rect(pixels, width, height, rect, white);
+                }
+            }
+        }
+    }
+}
+
+/// Draws the frames per second counter over a dark box in the top-left corner.
+pub fn draw_fps(pixels: &mut [Color], width: u32, height: u32, fps: f64) {
+    const MARGIN: u32 = 4;
+
+    let text = format!("{:.0} FPS", fps);
+    let text_width = text.chars().count() as u32 * 4 * TINY_SCALE;
+    let backdrop = (0, 0, text_width + 2 * MARGIN, 5 * TINY_SCALE + 2 * MARGIN);
+    fill_rect(pixels, width, height, backdrop, Color { r: 0, g: 0, b: 0 });
+    draw_tiny_text(pixels, width, height, MARGIN, MARGIN, &text);
+}
@@ -0,0 +1,429 @@
+//! This module implements the shape primitives drawn on top of the framebuffer.
+
+use crate::{DrawCall, ScreenContextManager};
+
+/// Shape drawn where two segments of a stroke meet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Extend the outer edges until they meet in a sharp corner, falling back to
+    /// [`Bevel`](LineJoin::Bevel) when the corner would be longer than the miter limit.
+    Miter,
+    /// Round the corner off with a circle centered on the vertex.
+    Round,
+    /// Cut the corner off with a straight line between the outer edges.
+    Bevel,
+}
+
+/// Shape drawn at both ends of an open stroke.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    /// End the stroke exactly at its end points.
+    Butt,
+    /// Add a half circle past each end point.
+    Round,
+    /// Extend the stroke past each end point by half its width.
+    Square,
+}
+
+/// Decides which parts of a self-intersecting or nested polygon count as its inside.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FillRule {
+    /// A point is inside if the polygon winds around it a non-zero amount of times, so
+    /// overlapping parts drawn in the same direction stay filled.
+    NonZero,
+    /// A point is inside if a ray from it crosses the polygon an odd amount of times, so
+    /// overlapping parts punch holes into each other.
+    EvenOdd,
+}
+
+/// Arithmetic the rasterizers compute coverage and edges with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RasterMode {
+    /// Floating point, which is the fastest but may round differently across platforms and
+    /// compilers.
+    FloatingPoint,
+    /// Integer arithmetic on coordinates snapped to 1/256 of a pixel, so the same drawing
+    /// produces bit-identical pixels everywhere. Meant for golden image tests.
+    FixedPoint,
+}
+
+/// How thick lines are drawn by [`stroke_polyline`](ScreenContextManager::stroke_polyline) and
+/// [`stroke_polygon`](ScreenContextManager::stroke_polygon).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StrokeStyle {
+    /// Thickness of the stroke in pixels.
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Longest a miter join may get, as a multiple of half the stroke width.
+    pub miter_limit: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        StrokeStyle {
+            width: 1.0,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+        }
+    }
+}
+
+pub(crate) type Point = (f32, f32);
+
+fn add(a: Point, b: Point) -> Point {
+    (a.0 + b.0, a.1 + b.1)
+}
+
+fn sub(a: Point, b: Point) -> Point {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn scale(a: Point, factor: f32) -> Point {
+    (a.0 * factor, a.1 * factor)
+}
+
+/// Unit vector from `a` to `b`, `None` if they are the same point.
+fn direction(a: Point, b: Point) -> Option<Point> {
+    let (dx, dy) = sub(b, a);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length > f32::EPSILON {
+        Some((dx / length, dy / length))
+    } else {
+        None
+    }
+}
+
+impl ScreenContextManager {
+    /// Switches the arithmetic used by the anti-aliased, polygon, stroke and fractional point
+    /// rasterizers. The default is [`RasterMode::FloatingPoint`].
+    pub fn set_raster_mode(&mut self, mode: RasterMode) {
+        self.raster_mode = mode;
+    }
+
+    /// Draws a thick open line through `points` with the current color, using the joins and caps
+    /// of `style`.
+    pub fn stroke_polyline(&mut self, points: &[(f32, f32)], style: &StrokeStyle) {
+        self.report(|| DrawCall::Stroke {
+            points: points.to_vec(),
+            style: *style,
+            closed: false,
+        });
+        self.stroke(points, style, false);
+    }
+
+    /// Draws the thick outline of the closed polygon through `points` with the current color,
+    /// using the joins of `style`.
+    pub fn stroke_polygon(&mut self, points: &[(f32, f32)], style: &StrokeStyle) {
+        self.report(|| DrawCall::Stroke {
+            points: points.to_vec(),
+            style: *style,
+            closed: true,
+        });
+        self.stroke(points, style, true);
+    }
+
+    /// Fills the polygon through `points` with the current color, deciding what is inside
+    /// according to `rule`. A pixel is filled when its center, which lies on integer
+    /// coordinates, is inside.
+    pub fn fill_polygon(&mut self, points: &[(f32, f32)], rule: FillRule) {
+        self.report(|| DrawCall::FillPolygon {
+            points: points.to_vec(),
+            rule,
+        });
+        self.fill_contours(&[points], rule);
+    }
+
+    pub(crate) fn stroke(&mut self, points: &[Point], style: &StrokeStyle, closed: bool) {
+        // Repeated points would make for zero-length segments without a direction
+        let mut path: Vec<Point> = Vec::with_capacity(points.len() + 1);
+        for &point in points {
+            if path
+                .last()
+                .is_none_or(|&last| direction(last, point).is_some())
+            {
+                path.push(point);
+            }
+        }
+        if closed && path.len() > 2 && direction(path[path.len() - 1], path[0]).is_none() {
+            path.pop();
+        }
+
+        let half = style.width / 2.0;
+        if path.is_empty() || half <= 0.0 {
+            return;
+        }
+        if path.len() == 1 {
+            // A lone point still gets its caps
+            match style.cap {
+                LineCap::Butt => {}
+                LineCap::Round => self.fill_disc(path[0], half),
+                LineCap::Square => {
+                    let (x, y) = path[0];
+                    self.fill_points(&[
+                        (x - half, y - half),
+                        (x + half, y - half),
+                        (x + half, y + half),
+                        (x - half, y + half),
+                    ]);
+                }
+            }
+            return;
+        }
+
+        let segment_count = if closed { path.len() } else { path.len() - 1 };
+        for i in 0..segment_count {
+            let (a, b) = (path[i], path[(i + 1) % path.len()]);
+            let d = direction(a, b).expect("repeated points were removed");
+            let n = (-d.1 * half, d.0 * half);
+            self.fill_points(&[add(a, n), add(b, n), sub(b, n), sub(a, n)]);
+        }
+
+        let joins = if closed {
+            0..path.len()
+        } else {
+            1..path.len() - 1
+        };
+        for i in joins {
+            let previous = path[(i + path.len() - 1) % path.len()];
+            let (vertex, next) = (path[i], path[(i + 1) % path.len()]);
+            self.stroke_join(previous, vertex, next, half, style);
+        }
+
+        if !closed {
+            let last = path.len() - 1;
+            self.stroke_cap(path[1], path[0], half, style.cap);
+            self.stroke_cap(path[last - 1], path[last], half, style.cap);
+        }
+    }
+
+    /// Fills the corner at `vertex` between the segments coming from `previous` and going to
+    /// `next`.
+    fn stroke_join(
+        &mut self,
+        previous: Point,
+        vertex: Point,
+        next: Point,
+        half: f32,
+        style: &StrokeStyle,
+    ) {
+        let (d1, d2) = match (direction(previous, vertex), direction(vertex, next)) {
+            (Some(d1), Some(d2)) => (d1, d2),
+            _ => return,
+        };
+        if style.join == LineJoin::Round {
+            self.fill_disc(vertex, half);
+            return;
+        }
+
+        // The gap between both segments opens up on the side opposite to where the path turns
+        let turn = d1.0 * d2.1 - d1.1 * d2.0;
+        if turn.abs() <= f32::EPSILON {
+            return;
+        }
+        let side = -turn.signum();
+        let n1 = scale((-d1.1, d1.0), half * side);
+        let n2 = scale((-d2.1, d2.0), half * side);
+
+        if style.join == LineJoin::Miter {
+            // The miter tip lies along the bisector of both offsets, further out the sharper the
+            // corner is
+            let bisector = add(n1, n2);
+            let bisector_length = (bisector.0 * bisector.0 + bisector.1 * bisector.1).sqrt();
+            if bisector_length > f32::EPSILON {
+                let cos_half_angle = bisector_length / (2.0 * half);
+                let miter_length = half / cos_half_angle;
+                if miter_length <= style.miter_limit * half {
+                    let tip = add(vertex, scale(bisector, miter_length / bisector_length));
+                    self.fill_points(&[vertex, add(vertex, n1), tip, add(vertex, n2)]);
+                    return;
+                }
+            }
+        }
+
+        self.fill_points(&[vertex, add(vertex, n1), add(vertex, n2)]);
+    }
+
+    /// Adds the cap at `end`, the stroke arriving there from `from`.
+    fn stroke_cap(&mut self, from: Point, end: Point, half: f32, cap: LineCap) {
+        let d = match direction(from, end) {
+            Some(d) => d,
+            None => return,
+        };
+        match cap {
+            LineCap::Butt => {}
+            LineCap::Round => self.fill_disc(end, half),
+            LineCap::Square => {
+                let n = (-d.1 * half, d.0 * half);
+                let far = add(end, scale(d, half));
+                self.fill_points(&[add(end, n), add(far, n), sub(far, n), sub(end, n)]);
+            }
+        }
+    }
+
+    /// Fills the polygon through `points` with the current color using the non-zero winding rule.
+    fn fill_points(&mut self, points: &[Point]) {
+        self.fill_contours(&[points], FillRule::NonZero);
+    }
+
+    /// Fills the shape made up of all the closed `contours` at once, so that the fill rule can
+    /// decide which overlapping parts are holes.
+    pub(crate) fn fill_contours<C: AsRef<[Point]>>(&mut self, contours: &[C], rule: FillRule) {
+        if self.is_untransformed() {
+            return self.fill_screen_contours(contours, rule);
+        }
+        let contours: Vec<Vec<Point>> = contours
+            .iter()
+            .map(|contour| {
+                let points = contour.as_ref().iter();
+                points.map(|&(x, y)| self.world_to_screen(x, y)).collect()
+            })
+            .collect();
+        self.fill_screen_contours(&contours, rule);
+    }
+
+    /// Like `fill_contours`, with the contours already in framebuffer coordinates.
+    fn fill_screen_contours<C: AsRef<[Point]>>(&mut self, contours: &[C], rule: FillRule) {
+        if self.raster_mode == RasterMode::FixedPoint {
+            return self.fill_contours_fixed(contours, rule);
+        }
+        let all_points = || contours.iter().flat_map(|contour| contour.as_ref().iter());
+        if self.height == 0 || all_points().next().is_none() {
+            return;
+        }
+
+        let min_y = all_points().map(|p| p.1).fold(f32::INFINITY, f32::min);
+        let max_y = all_points().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+        let first_row = min_y.ceil().max(0.0) as u32;
+        let last_row = (max_y.ceil() - 1.0).min(self.height as f32 - 1.0);
+        if last_row < 0.0 {
+            return;
+        }
+
+        let mut crossings: Vec<(f32, i32)> = Vec::new();
+        for row in first_row..=last_row as u32 {
+            let y = row as f32;
+            crossings.clear();
+            for points in contours.iter().map(AsRef::as_ref) {
+                for (i, &a) in points.iter().enumerate() {
+                    let b = points[(i + 1) % points.len()];
+                    // Half-open on y so vertices shared by two edges are only counted once
+                    let (winding, top, bottom) = if a.1 < b.1 { (1, a, b) } else { (-1, b, a) };
+                    if y >= top.1 && y < bottom.1 {
+                        let t = (y - top.1) / (bottom.1 - top.1);
+                        crossings.push((top.0 + t * (bottom.0 - top.0), winding));
+                    }
+                }
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                let inside = match rule {
+                    FillRule::NonZero => winding != 0,
+                    FillRule::EvenOdd => winding % 2 != 0,
+                };
+                if inside {
+                    self.fill_span(row, pair[0].0, pair[1].0);
+                }
+            }
+        }
+    }
+
+    /// Fills the pixels of `row` whose centers lie in [`from`, `to`).
+    fn fill_span(&mut self, row: u32, from: f32, to: f32) {
+        let clip = self.clip_bounds();
+        let start = from.ceil().max(clip.left as f32);
+        let end = to.ceil().min(clip.right as f32);
+        if !clip.contains_row(row as i64) || start >= end {
+            return;
+        }
+        let offset = (row * self.width) as usize;
+        self.framebuffer[offset + start as usize..offset + end as usize].fill(self.color);
+    }
+
+    /// Fills a solid circle with the current color.
+    pub(crate) fn fill_disc(&mut self, center: Point, radius: f32) {
+        let (center, radius) = (
+            self.world_to_screen(center.0, center.1),
+            radius * self.camera.zoom,
+        );
+        if self.raster_mode == RasterMode::FixedPoint {
+            return self.fill_disc_fixed(center, radius);
+        }
+        let first_row = (center.1 - radius).ceil().max(0.0) as u32;
+        let last_row = (center.1 + radius).floor().min(self.height as f32 - 1.0);
+        if last_row < 0.0 {
+            return;
+        }
+        for row in first_row..=last_row as u32 {
+            let dy = row as f32 - center.1;
+            let dx = (radius * radius - dy * dy).max(0.0).sqrt();
+            // Inclusive on both sides, like the rows
+            self.fill_span(row, center.0 - dx, (center.0 + dx).floor() + 1.0);
+        }
+    }
+
+    /// Draws the outline of a circle with anti-aliased edges, blending the current color into the
+    /// framebuffer according to how much of each pixel the outline covers.
+    pub fn draw_circle_aa(&mut self, center_x: f32, center_y: f32, radius: f32) {
+        self.draw_ellipse_aa(center_x, center_y, radius, radius);
+    }
+
+    /// Draws the outline of an axis-aligned ellipse with anti-aliased edges, using Xiaolin Wu's
+    /// approach of splitting every step's intensity between the two pixels straddling the curve.
+    pub fn draw_ellipse_aa(&mut self, center_x: f32, center_y: f32, radius_x: f32, radius_y: f32) {
+        self.report(|| DrawCall::Ellipse {
+            center: (center_x, center_y),
+            radius: (radius_x, radius_y),
+        });
+        let (center_x, center_y) = self.world_to_screen(center_x, center_y);
+        let (radius_x, radius_y) = (radius_x * self.camera.zoom, radius_y * self.camera.zoom);
+        if self.raster_mode == RasterMode::FixedPoint {
+            return self.draw_ellipse_aa_fixed(center_x, center_y, radius_x, radius_y);
+        }
+        if radius_x <= 0.0 || radius_y <= 0.0 {
+            return;
+        }
+
+        // Where the slope of the curve is 45°: before it, stepping along x hits every column once,
+        // after it stepping along y does the same for every row.
+        let diagonal = (radius_x * radius_x + radius_y * radius_y).sqrt();
+        let x_limit = radius_x * radius_x / diagonal;
+        let y_limit = radius_y * radius_y / diagonal;
+
+        for column in (center_x - x_limit).ceil() as i64..=(center_x + x_limit).floor() as i64 {
+            let dx = column as f32 - center_x;
+            let dy = radius_y * (1.0 - (dx * dx) / (radius_x * radius_x)).max(0.0).sqrt();
+            for y in [center_y - dy, center_y + dy] {
+                self.plot_wu_step(column, y, false);
+            }
+        }
+
+        for row in (center_y - y_limit).ceil() as i64..=(center_y + y_limit).floor() as i64 {
+            let dy = row as f32 - center_y;
+            let dx = radius_x * (1.0 - (dy * dy) / (radius_y * radius_y)).max(0.0).sqrt();
+            for x in [center_x - dx, center_x + dx] {
+                self.plot_wu_step(row, x, true);
+            }
+        }
+    }
+
+    /// Splits one step of a curve at fractional position `along` between the two pixels that
+    /// straddle it on the line `fixed`. `fixed` is a column, or a row when `transposed` is set.
+    fn plot_wu_step(&mut self, fixed: i64, along: f32, transposed: bool) {
+        let near = along.floor();
+        let fraction = along - near;
+        let near = near as i64;
+
+        for (offset, coverage) in [(0, 1.0 - fraction), (1, fraction)] {
+            if transposed {
+                self.blend_pixel(near + offset, fixed, coverage);
+            } else {
+                self.blend_pixel(fixed, near + offset, coverage);
+            }
+        }
+    }
+}
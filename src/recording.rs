@@ -0,0 +1,448 @@
+//! This module records the presented frames, passing them to a sink that encodes or writes them
+//! somewhere.
+
+use crate::{Color, RecordingError, ScreenContextManager};
+use std::io::Write;
+#[cfg(any(feature = "gif", feature = "apng", feature = "ffmpeg"))]
+use std::path::Path;
+#[cfg(feature = "ffmpeg")]
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+#[cfg(any(feature = "gif", feature = "apng"))]
+use std::{fs::File, io::BufWriter};
+#[cfg(feature = "gif")]
+use {
+    image::codecs::gif::{GifEncoder, Repeat},
+    image::{Delay, Frame, RgbaImage},
+};
+
+/// How [`record_raw`](ScreenContextManager::record_raw) writes frames.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RawFormat {
+    /// Raw RGB24 pixels row by row, one frame after the other without any header, e.g. for
+    /// `ffmpeg -f rawvideo -pixel_format rgb24 -video_size WxH -i -`.
+    Rgb24,
+    /// A YUV4MPEG2 stream in 4:4:4 at the given frame rate, which carries its own size and rate
+    /// so that e.g. `ffmpeg -i -` can read it as is.
+    Y4m { fps: u32 },
+}
+
+/// Receives the presented frames while recording.
+pub(crate) trait FrameSink {
+    /// Writes a frame that stayed on screen for `duration`.
+    fn write_frame(
+        &mut self,
+        pixels: &[Color],
+        width: u32,
+        height: u32,
+        duration: Duration,
+    ) -> Result<(), RecordingError>;
+
+    /// Completes the recording once the last frame was written.
+    fn finish(self: Box<Self>) -> Result<(), RecordingError>;
+}
+
+/// A recording in progress.
+pub(crate) struct Recording {
+    sink: Box<dyn FrameSink>,
+    // The last presented frame is only written once it's known how long it stayed on screen
+    last_frame: Vec<Color>,
+    last_size: (u32, u32),
+    last_present: Option<Instant>,
+    // The first error is kept for stop_recording to report, no frames are written after it
+    error: Option<RecordingError>,
+}
+
+impl Recording {
+    pub fn new(sink: Box<dyn FrameSink>) -> Recording {
+        Recording {
+            sink,
+            last_frame: Vec::new(),
+            last_size: (0, 0),
+            last_present: None,
+            error: None,
+        }
+    }
+
+    /// Records a frame that was just presented.
+    pub fn push(&mut self, pixels: &[Color], width: u32, height: u32) {
+        let now = Instant::now();
+        self.write_last(now);
+        self.last_frame.clear();
+        self.last_frame.extend_from_slice(pixels);
+        self.last_size = (width, height);
+        self.last_present = Some(now);
+    }
+
+    /// Writes out the last frame, which stayed on screen until `until`.
+    fn write_last(&mut self, until: Instant) {
+        let last_present = match self.last_present {
+            Some(last_present) => last_present,
+            None => return,
+        };
+        if self.error.is_none() {
+            let (width, height) = self.last_size;
+            let duration = until - last_present;
+            let written = self
+                .sink
+                .write_frame(&self.last_frame, width, height, duration);
+            self.error = written.err();
+        }
+    }
+
+    /// Writes the last frame and completes the recording. The sink is finished even after an
+    /// error, so that e.g. ffmpeg is always waited for, but the first error is what's reported.
+    pub fn finish(mut self) -> Result<(), RecordingError> {
+        self.write_last(Instant::now());
+        let finished = self.sink.finish();
+        match self.error {
+            Some(error) => Err(error),
+            None => finished,
+        }
+    }
+}
+
+/// Writes the frames uncompressed to a stream.
+struct RawSink {
+    writer: Box<dyn Write>,
+    format: RawFormat,
+    size: Option<(u32, u32)>,
+    // Planes of the frame being converted to YUV, kept between frames
+    planes: Vec<u8>,
+}
+
+impl FrameSink for RawSink {
+    fn write_frame(
+        &mut self,
+        pixels: &[Color],
+        width: u32,
+        height: u32,
+        _duration: Duration,
+    ) -> Result<(), RecordingError> {
+        match self.size {
+            None => {
+                if let RawFormat::Y4m { fps } = self.format {
+                    let header =
+                        format!("YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444\n", width, height, fps);
+                    self.writer.write_all(header.as_bytes())?;
+                }
+                self.size = Some((width, height));
+            }
+            Some(size) if size != (width, height) => return Err(RecordingError::FrameSize),
+            Some(_) => {}
+        }
+
+        match self.format {
+            RawFormat::Rgb24 => self.writer.write_all(bytemuck::cast_slice(pixels))?,
+            RawFormat::Y4m { .. } => {
+                // BT.601 in limited range, which players assume for YUV4MPEG2
+                let count = pixels.len();
+                self.planes.resize(3 * count, 0);
+                for (i, pixel) in pixels.iter().enumerate() {
+                    let (r, g, b) = (pixel.r as i32, pixel.g as i32, pixel.b as i32);
+                    self.planes[i] = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+                    self.planes[count + i] =
+                        (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8;
+                    self.planes[2 * count + i] =
+                        (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+                }
+                self.writer.write_all(b"FRAME\n")?;
+                self.writer.write_all(&self.planes)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), RecordingError> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Pipes the frames into an ffmpeg process encoding them into a video.
+#[cfg(feature = "ffmpeg")]
+struct FfmpegSink {
+    raw: RawSink,
+    child: Child,
+}
+
+#[cfg(feature = "ffmpeg")]
+impl FrameSink for FfmpegSink {
+    fn write_frame(
+        &mut self,
+        pixels: &[Color],
+        width: u32,
+        height: u32,
+        duration: Duration,
+    ) -> Result<(), RecordingError> {
+        self.raw.write_frame(pixels, width, height, duration)
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), RecordingError> {
+        let FfmpegSink { raw, mut child } = *self;
+        // Closing ffmpeg's input is what tells it the video is over
+        Box::new(raw).finish()?;
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(RecordingError::Ffmpeg(status));
+        }
+        Ok(())
+    }
+}
+
+/// Encodes the frames into a looping animated GIF.
+#[cfg(feature = "gif")]
+struct GifSink {
+    encoder: GifEncoder<BufWriter<File>>,
+    size: Option<(u32, u32)>,
+}
+
+#[cfg(feature = "gif")]
+impl FrameSink for GifSink {
+    fn write_frame(
+        &mut self,
+        pixels: &[Color],
+        width: u32,
+        height: u32,
+        duration: Duration,
+    ) -> Result<(), RecordingError> {
+        if *self.size.get_or_insert((width, height)) != (width, height) {
+            return Err(RecordingError::FrameSize);
+        }
+        let rgba = pixels
+            .iter()
+            .flat_map(|pixel| [pixel.r, pixel.g, pixel.b, 255])
+            .collect();
+        let image = RgbaImage::from_raw(width, height, rgba).ok_or(RecordingError::FrameSize)?;
+        let delay = Delay::from_saturating_duration(duration);
+        Ok(self
+            .encoder
+            .encode_frame(Frame::from_parts(image, 0, 0, delay))?)
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), RecordingError> {
+        // The encoder writes the trailer when dropped
+        Ok(())
+    }
+}
+
+/// Encodes the frames into a looping animated PNG, in full color. The frames are compressed as
+/// they come, but can only be written out once their count is known, so they are kept in memory
+/// until the recording is stopped.
+#[cfg(feature = "apng")]
+struct ApngSink {
+    file: BufWriter<File>,
+    size: Option<(u32, u32)>,
+    // The compressed image data of every frame, with its duration
+    frames: Vec<(Vec<u8>, Duration)>,
+}
+
+#[cfg(feature = "apng")]
+impl ApngSink {
+    /// Compresses a frame the way PNG stores image data, by encoding it as a standalone PNG and
+    /// keeping the contents of its data chunks.
+    fn compress(pixels: &[Color], width: u32, height: u32) -> Result<Vec<u8>, RecordingError> {
+        let mut png = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut png, width, height);
+            encoder.set_color(png::ColorType::RGB);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(bytemuck::cast_slice(pixels))?;
+        }
+
+        let mut data = Vec::new();
+        // Past the signature, chunks are a big-endian length, a type, the data and a checksum
+        let mut chunks = &png[8..];
+        while chunks.len() >= 12 {
+            let length = u32::from_be_bytes([chunks[0], chunks[1], chunks[2], chunks[3]]) as usize;
+            if &chunks[4..8] == b"IDAT" {
+                data.extend_from_slice(&chunks[8..8 + length]);
+            }
+            chunks = &chunks[12 + length..];
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(feature = "apng")]
+impl FrameSink for ApngSink {
+    fn write_frame(
+        &mut self,
+        pixels: &[Color],
+        width: u32,
+        height: u32,
+        duration: Duration,
+    ) -> Result<(), RecordingError> {
+        if *self.size.get_or_insert((width, height)) != (width, height) {
+            return Err(RecordingError::FrameSize);
+        }
+        let data = Self::compress(pixels, width, height)?;
+        self.frames.push((data, duration));
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), RecordingError> {
+        let (width, height) = match self.size {
+            Some(size) => size,
+            None => return Err(RecordingError::NoFrames),
+        };
+        let mut encoder = png::Encoder::new(self.file, width, height);
+        encoder.set_color(png::ColorType::RGB);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+
+        // Frame count, then how many times to play them with 0 looping forever
+        let mut control = (self.frames.len() as u32).to_be_bytes().to_vec();
+        control.extend_from_slice(&0u32.to_be_bytes());
+        writer.write_chunk(*b"acTL", &control)?;
+
+        let mut sequence = 0u32;
+        for (i, (data, duration)) in self.frames.iter().enumerate() {
+            let mut frame_control = Vec::with_capacity(26);
+            frame_control.extend_from_slice(&sequence.to_be_bytes());
+            for value in [width, height, 0, 0] {
+                frame_control.extend_from_slice(&value.to_be_bytes());
+            }
+            // Delay as a fraction of milliseconds over 1000
+            let delay = duration.as_millis().min(u16::MAX as u128) as u16;
+            frame_control.extend_from_slice(&delay.to_be_bytes());
+            frame_control.extend_from_slice(&1000u16.to_be_bytes());
+            // No disposal, and frames replace what's underneath instead of blending over it
+            frame_control.extend_from_slice(&[0, 0]);
+            writer.write_chunk(*b"fcTL", &frame_control)?;
+            sequence += 1;
+
+            // The first frame doubles as the default image shown by viewers without APNG support
+            if i == 0 {
+                writer.write_chunk(*b"IDAT", data)?;
+            } else {
+                let mut frame_data = sequence.to_be_bytes().to_vec();
+                frame_data.extend_from_slice(data);
+                writer.write_chunk(*b"fdAT", &frame_data)?;
+                sequence += 1;
+            }
+        }
+        // The writer adds the end chunk when dropped
+        Ok(())
+    }
+}
+
+impl ScreenContextManager {
+    /// Starts passing every presented frame to `sink`, finishing any recording in progress.
+    pub(crate) fn start_recording(
+        &mut self,
+        sink: Box<dyn FrameSink>,
+    ) -> Result<(), RecordingError> {
+        self.stop_recording()?;
+        self.recording = Some(Recording::new(sink));
+        Ok(())
+    }
+
+    /// Starts recording every presented frame, overlays included, into a looping animated GIF
+    /// at `path`. Each frame is shown for as long as it stayed on screen. Frames are quantized
+    /// to 256 colors as they are presented, which takes a while for large framebuffers. The
+    /// framebuffer must keep the same size while recording.
+    #[cfg(feature = "gif")]
+    pub fn record_gif<P: AsRef<Path>>(&mut self, path: P) -> Result<(), RecordingError> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = GifEncoder::new_with_speed(file, 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+        self.start_recording(Box::new(GifSink {
+            encoder,
+            size: None,
+        }))
+    }
+
+    /// Starts recording every presented frame, overlays included, into a looping animated PNG
+    /// at `path`. Unlike GIFs these keep full 24-bit color, but the whole recording is kept in
+    /// memory (compressed) until it is stopped with
+    /// [`stop_recording`](ScreenContextManager::stop_recording) or the screen is dropped. The
+    /// framebuffer must keep the same size while recording.
+    #[cfg(feature = "apng")]
+    pub fn record_apng<P: AsRef<Path>>(&mut self, path: P) -> Result<(), RecordingError> {
+        let file = BufWriter::new(File::create(path)?);
+        self.start_recording(Box::new(ApngSink {
+            file,
+            size: None,
+            frames: Vec::new(),
+        }))
+    }
+
+    /// Starts writing every presented frame, overlays included, to `writer` in the given raw
+    /// format, e.g. to pipe the frames into a video encoder's standard input. The framebuffer
+    /// must keep the same size while recording.
+    pub fn record_raw<W: Write + 'static>(
+        &mut self,
+        writer: W,
+        format: RawFormat,
+    ) -> Result<(), RecordingError> {
+        self.start_recording(Box::new(RawSink {
+            writer: Box::new(writer),
+            format,
+            size: None,
+            planes: Vec::new(),
+        }))
+    }
+
+    /// Starts encoding every presented frame, overlays included, into a video at `path` playing
+    /// at `fps` frames per second, by running the `ffmpeg` executable found on the `PATH`. The
+    /// container and codec follow from the extension, e.g. `.mp4` or `.webm`. Every presented
+    /// frame becomes one video frame, regardless of how long it stayed on screen, and the
+    /// framebuffer must keep the same size while recording. Stopping the recording, or dropping
+    /// the screen, waits for ffmpeg to finish writing the video.
+    #[cfg(feature = "ffmpeg")]
+    pub fn start_video_capture<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        fps: u32,
+    ) -> Result<(), RecordingError> {
+        // Stopped first, since an ffmpeg already running would be left behind if this failed
+        self.stop_recording()?;
+        let mut child = Command::new("ffmpeg")
+            .args([
+                "-loglevel",
+                "error",
+                "-y",
+                "-f",
+                "rawvideo",
+                "-pixel_format",
+                "rgb24",
+            ])
+            .args(["-video_size", &format!("{}x{}", self.width, self.height)])
+            .args(["-framerate", &fps.to_string(), "-i", "-"])
+            // Most players need 4:2:0 chroma, which needs even dimensions
+            .args([
+                "-vf",
+                "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+                "-pix_fmt",
+                "yuv420p",
+            ])
+            .arg(path.as_ref())
+            .stdin(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("ffmpeg's input is piped");
+        let raw = RawSink {
+            writer: Box::new(stdin),
+            format: RawFormat::Rgb24,
+            size: None,
+            planes: Vec::new(),
+        };
+        self.start_recording(Box::new(FfmpegSink { raw, child }))
+    }
+
+    /// Stops the recording in progress, if any, and completes the file. Errors that happened
+    /// while frames were being recorded are reported here. A recording still in progress when
+    /// the screen is dropped is completed then, but any error is lost, so call this explicitly
+    /// to find out whether the file was written.
+    pub fn stop_recording(&mut self) -> Result<(), RecordingError> {
+        match self.recording.take() {
+            Some(recording) => recording.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns whether the presented frames are being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+}
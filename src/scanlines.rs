@@ -0,0 +1,50 @@
+//! This module hands out the rows of the framebuffer directly, for renderers that produce a whole
+//! scanline at a time.
+
+use crate::{Color, ScreenContextManager};
+
+impl ScreenContextManager {
+    /// Calls `render` for every row of the framebuffer from top to bottom, with the row's `y` and
+    /// its pixels from left to right.
+    pub fn render_scanlines<F>(&mut self, mut render: F)
+    where
+        F: FnMut(u32, &mut [Color]),
+    {
+        if self.width == 0 {
+            return;
+        }
+        for (y, row) in self
+            .framebuffer
+            .chunks_exact_mut(self.width as usize)
+            .enumerate()
+        {
+            render(y as u32, row);
+        }
+    }
+
+    /// Like [`render_scanlines`](ScreenContextManager::render_scanlines), but rendering bands of
+    /// rows on all available cores at once, so rows are visited in no particular order.
+    pub fn render_scanlines_parallel<F>(&mut self, render: F)
+    where
+        F: Fn(u32, &mut [Color]) + Sync,
+    {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+        let band_height = (self.height as usize).div_ceil(threads);
+        let band_length = band_height * self.width as usize;
+        let width = self.width as usize;
+        let render = &render;
+
+        std::thread::scope(|scope| {
+            for (band, pixels) in self.framebuffer.chunks_mut(band_length).enumerate() {
+                scope.spawn(move || {
+                    for (i, row) in pixels.chunks_exact_mut(width).enumerate() {
+                        render((band * band_height + i) as u32, row);
+                    }
+                });
+            }
+        });
+    }
+}
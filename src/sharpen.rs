@@ -0,0 +1,92 @@
+//! This module implements the sharpening pass applied to the presented frame, countering the blur
+//! of linear upscaling.
+
+use crate::{Color, ScreenContextManager};
+
+/// Sharpens `source` into `target` with a contrast-adaptive kernel: every pixel is pushed away
+/// from its four neighbors, less so where the neighborhood already has a lot of contrast, so that
+/// soft edges get crisper without hard ones ringing. `strength` goes from 0 to 1.
+pub fn sharpen(source: &[Color], target: &mut [Color], width: u32, height: u32, strength: f32) {
+    let (width, height) = (width as usize, height as usize);
+    // How much the neighbors are subtracted at full amplification
+    let peak = -1.0 / (8.0 - 3.0 * strength.clamp(0.0, 1.0));
+
+    for y in 0..height {
+        for x in 0..width {
+            let at = |x: usize, y: usize| source[y * width + x];
+            let center = at(x, y);
+            let neighbors = [
+                at(x, y.saturating_sub(1)),
+                at(x.saturating_sub(1), y),
+                at((x + 1).min(width - 1), y),
+                at(x, (y + 1).min(height - 1)),
+            ];
+
+            let channel = |get: fn(Color) -> u8| {
+                let center = get(center) as f32 / 255.0;
+                let values = neighbors.map(|neighbor| get(neighbor) as f32 / 255.0);
+                let min = values.iter().fold(center, |min, &v| min.min(v));
+                let max = values.iter().fold(center, |max, &v| max.max(v));
+                // Nothing to sharpen in pure black
+                if max <= 0.0 {
+                    return 0;
+                }
+                let amplification = (min.min(1.0 - max) / max).clamp(0.0, 1.0).sqrt();
+                let weight = amplification * peak;
+                let sum: f32 = values.iter().sum();
+                let sharpened = (center + weight * sum) / (1.0 + 4.0 * weight);
+                (sharpened.clamp(0.0, 1.0) * 255.0).round() as u8
+            };
+            target[y * width + x] = Color {
+                r: channel(|c| c.r),
+                g: channel(|c| c.g),
+                b: channel(|c| c.b),
+            };
+        }
+    }
+}
+
+impl ScreenContextManager {
+    /// Sharpens every presented frame with the given strength from 0 to 1, or stops doing so with
+    /// `None`. Meant for small framebuffers upscaled with
+    /// [`ScaleFilter::Linear`](crate::ScaleFilter::Linear), which otherwise look blurry at
+    /// non-integer scales. The framebuffer itself is left untouched.
+    pub fn set_sharpening(&mut self, strength: Option<f32>) {
+        self.sharpening = strength;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sharpened(source: &[Color], width: u32, height: u32, strength: f32) -> Vec<Color> {
+        let mut target = vec![Color::default(); source.len()];
+        sharpen(source, &mut target, width, height, strength);
+        target
+    }
+
+    #[test]
+    fn flat_areas_stay_flat() {
+        for value in [0, 1, 128, 255] {
+            let source = vec![Color::new(value, value, value); 12];
+            assert_eq!(sharpened(&source, 4, 3, 1.0), source);
+        }
+    }
+
+    #[test]
+    fn soft_edges_get_more_contrast() {
+        let row = [60, 60, 100, 140, 140].map(|v| Color::new(v, v, v));
+        let target = sharpened(&row, 5, 1, 1.0);
+        assert!(target[1].r < 60 && target[3].r > 140);
+        assert_eq!(target[2], row[2]);
+    }
+
+    #[test]
+    fn stronger_sharpening_pushes_further() {
+        let row = [60, 60, 100, 140, 140].map(|v| Color::new(v, v, v));
+        let weak = sharpened(&row, 5, 1, 0.0);
+        let strong = sharpened(&row, 5, 1, 1.0);
+        assert!(strong[1].r < weak[1].r && strong[3].r > weak[3].r);
+    }
+}
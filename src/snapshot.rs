@@ -0,0 +1,89 @@
+//! This module saves and restores the whole framebuffer, e.g. for the undo stack of a paint
+//! program or the fixtures of a test.
+
+use crate::{Color, ScreenContextManager};
+
+/// A copy of the framebuffer taken with [`snapshot`](ScreenContextManager::snapshot), to put it
+/// back later with [`restore_snapshot`](ScreenContextManager::restore_snapshot).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameSnapshot {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl FrameSnapshot {
+    /// Creates a snapshot from raw RGB24 pixels, row by row, as returned by
+    /// [`get_pixels`](FrameSnapshot::get_pixels). Returns `None` if there aren't exactly
+    /// `width`×`height` pixels.
+    pub fn from_pixels(width: u32, height: u32, pixels: &[u8]) -> Option<FrameSnapshot> {
+        let size = (width as usize)
+            .checked_mul(height as usize)?
+            .checked_mul(std::mem::size_of::<Color>())?;
+        if pixels.len() != size {
+            return None;
+        }
+        Some(FrameSnapshot {
+            width,
+            height,
+            pixels: bytemuck::cast_slice(pixels).to_vec(),
+        })
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the raw RGB24 pixels, row by row, e.g. to store the snapshot in a file.
+    pub fn get_pixels(&self) -> &[u8] {
+        bytemuck::cast_slice(&self.pixels)
+    }
+}
+
+impl ScreenContextManager {
+    /// Copies the framebuffer as it is right now.
+    pub fn snapshot(&self) -> FrameSnapshot {
+        FrameSnapshot {
+            width: self.width,
+            height: self.height,
+            pixels: self.framebuffer.clone(),
+        }
+    }
+
+    /// Puts the framebuffer back to the state saved in `snapshot`. If the snapshot has a
+    /// different size, the framebuffer is resized to it first, like
+    /// [`set_logical_size`](ScreenContextManager::set_logical_size) does.
+    pub fn restore_snapshot(&mut self, snapshot: &FrameSnapshot) {
+        if (snapshot.width, snapshot.height) != (self.width, self.height) {
+            self.set_logical_size(snapshot.width, snapshot.height);
+        }
+        self.framebuffer.copy_from_slice(&snapshot.pixels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixels_round_trip() {
+        let pixels: Vec<u8> = (0..18).collect();
+        let snapshot = FrameSnapshot::from_pixels(3, 2, &pixels).unwrap();
+        assert_eq!((snapshot.get_width(), snapshot.get_height()), (3, 2));
+        assert_eq!(snapshot.get_pixels(), pixels.as_slice());
+    }
+
+    #[test]
+    fn wrong_pixel_counts_are_rejected() {
+        assert_eq!(FrameSnapshot::from_pixels(3, 2, &[0; 17]), None);
+        assert_eq!(FrameSnapshot::from_pixels(3, 2, &[0; 24]), None);
+        assert_eq!(
+            FrameSnapshot::from_pixels(u32::MAX, u32::MAX, &[0; 3]),
+            None
+        );
+    }
+}
@@ -0,0 +1,339 @@
+//! This module plays sound through SDL's audio subsystem, generating and mixing everything on its
+//! callback thread.
+
+use crate::{AudioError, AudioTap, ScreenContextManager};
+use sdl2::audio::{
+    AudioCVT, AudioCallback, AudioDevice, AudioFormat, AudioSpecDesired, AudioSpecWAV,
+};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Loudness of generated tones, leaving headroom for several of them at once.
+const TONE_VOLUME: f32 = 0.25;
+/// Fade in and out of generated tones, so that they start and stop without clicking.
+const TONE_FADE: Duration = Duration::from_millis(5);
+
+/// Shape of the wave a tone is generated with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Waveform {
+    /// A pure tone.
+    Sine,
+    /// A harsher, retro sounding tone.
+    Square,
+}
+
+/// Voices that can play at once by default.
+const DEFAULT_VOICES: usize = 16;
+
+/// Identifies a sound or tone being played, to change its volume or stop it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VoiceId(u64);
+
+/// A tone being played.
+struct Tone {
+    waveform: Waveform,
+    // Cycles per sample, and how far into the current cycle the tone is
+    step: f32,
+    phase: f32,
+    played: u32,
+    length: u32,
+    fade: u32,
+}
+
+impl Tone {
+    /// Adds the tone's next samples to `out`.
+    fn mix(&mut self, out: &mut [f32], volume: f32) {
+        for sample in out {
+            if self.played == self.length {
+                return;
+            }
+            let value = match self.waveform {
+                Waveform::Sine => (self.phase * std::f32::consts::TAU).sin(),
+                Waveform::Square if self.phase < 0.5 => 1.0,
+                Waveform::Square => -1.0,
+            };
+            let edge = self.played.min(self.length - self.played);
+            let envelope = (edge as f32 / self.fade as f32).min(1.0);
+            *sample += value * envelope * TONE_VOLUME * volume;
+            self.phase = (self.phase + self.step).fract();
+            self.played += 1;
+        }
+    }
+}
+
+/// A sound decoded and converted to the audio device's format, ready to be played any number of
+/// times, see [`load_sound`](ScreenContextManager::load_sound). Cloning it is cheap, the samples
+/// are shared.
+#[derive(Clone, Debug)]
+pub struct Sound {
+    samples: Arc<[f32]>,
+    sample_rate: u32,
+}
+
+impl Sound {
+    /// Returns how long the sound plays for.
+    pub fn get_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.samples.len() as f64 / self.sample_rate as f64)
+    }
+}
+
+/// A sound being played.
+struct Playback {
+    samples: Arc<[f32]>,
+    position: usize,
+    looping: bool,
+}
+
+impl Playback {
+    /// Adds the sound's next samples to `out`, starting over at the end if looping.
+    fn mix(&mut self, out: &mut [f32], volume: f32) {
+        let mut written = 0;
+        while written < out.len() && self.position < self.samples.len() {
+            let left = &self.samples[self.position..];
+            let count = left.len().min(out.len() - written);
+            for (sample, value) in out[written..written + count].iter_mut().zip(left) {
+                *sample += value * volume;
+            }
+            written += count;
+            self.position += count;
+            if self.looping && self.position == self.samples.len() {
+                self.position = 0;
+            }
+        }
+    }
+}
+
+/// What a voice plays.
+enum Source {
+    Tone(Tone),
+    Sound(Playback),
+}
+
+/// One of the sounds or tones being mixed together.
+struct Voice {
+    id: VoiceId,
+    volume: f32,
+    source: Source,
+}
+
+impl Voice {
+    fn is_looping(&self) -> bool {
+        matches!(self.source, Source::Sound(Playback { looping: true, .. }))
+    }
+
+    fn is_finished(&self) -> bool {
+        match &self.source {
+            Source::Tone(tone) => tone.played == tone.length,
+            Source::Sound(playback) => playback.position == playback.samples.len(),
+        }
+    }
+}
+
+/// Mixes everything being played into SDL's audio buffers, on SDL's audio thread.
+pub(crate) struct Mixer {
+    sample_rate: u32,
+    voices: Vec<Voice>,
+    next_id: u64,
+    max_voices: usize,
+    volume: f32,
+    tap: Option<AudioTap>,
+}
+
+impl Mixer {
+    /// Starts playing `source` on a new voice. If all voices are taken, the oldest one is stopped
+    /// to make room, preferring sounds that don't loop since looping ones are usually music.
+    fn play(&mut self, source: Source) -> VoiceId {
+        if self.voices.len() >= self.max_voices {
+            let oldest = self.voices.iter().position(|voice| !voice.is_looping());
+            self.voices.remove(oldest.unwrap_or(0));
+        }
+        let id = VoiceId(self.next_id);
+        self.next_id += 1;
+        self.voices.push(Voice {
+            id,
+            volume: 1.0,
+            source,
+        });
+        id
+    }
+
+    fn voice(&mut self, id: VoiceId) -> Option<&mut Voice> {
+        self.voices.iter_mut().find(|voice| voice.id == id)
+    }
+}
+
+impl AudioCallback for Mixer {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        out.fill(0.0);
+        for voice in &mut self.voices {
+            let volume = voice.volume * self.volume;
+            match &mut voice.source {
+                Source::Tone(tone) => tone.mix(out, volume),
+                Source::Sound(playback) => playback.mix(out, volume),
+            }
+        }
+        self.voices.retain(|voice| !voice.is_finished());
+        for sample in out.iter_mut() {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+        if let Some(tap) = self.tap.as_ref() {
+            tap.push(out);
+        }
+    }
+}
+
+/// The opened audio device, playing mono sound.
+pub(crate) struct Speaker {
+    device: AudioDevice<Mixer>,
+}
+
+impl Speaker {
+    /// Makes the mixer feed `tap` with everything it plays.
+    pub fn set_tap(&mut self, tap: Option<AudioTap>) {
+        self.device.lock().tap = tap;
+    }
+}
+
+impl ScreenContextManager {
+    /// Returns the audio device, opening it the first time.
+    fn speaker(&mut self) -> Result<&mut Speaker, AudioError> {
+        if self.speaker.is_none() {
+            let sdl = self.output.canvas.window().subsystem().sdl();
+            let desired = AudioSpecDesired {
+                freq: Some(44_100),
+                channels: Some(1),
+                samples: Some(512),
+            };
+            let tap = self.audio_tap.clone();
+            let device = sdl.audio()?.open_playback(None, &desired, |spec| Mixer {
+                sample_rate: spec.freq as u32,
+                voices: Vec::new(),
+                next_id: 0,
+                max_voices: DEFAULT_VOICES,
+                volume: 1.0,
+                tap,
+            })?;
+            device.resume();
+            self.speaker = Some(Speaker { device });
+        }
+        Ok(self.speaker.as_mut().unwrap())
+    }
+
+    /// Plays a tone of the given waveform and frequency for `duration`, on top of anything
+    /// already playing. Returns right away, the tone is generated on SDL's audio thread.
+    pub fn play_tone(
+        &mut self,
+        waveform: Waveform,
+        frequency_hz: f32,
+        duration: Duration,
+    ) -> Result<VoiceId, AudioError> {
+        let mut mixer = self.speaker()?.device.lock();
+        let sample_rate = mixer.sample_rate as f32;
+        Ok(mixer.play(Source::Tone(Tone {
+            waveform,
+            step: frequency_hz / sample_rate,
+            phase: 0.0,
+            played: 0,
+            length: (duration.as_secs_f32() * sample_rate) as u32,
+            fade: ((TONE_FADE.as_secs_f32() * sample_rate) as u32).max(1),
+        })))
+    }
+
+    /// Plays a square wave beep of the given frequency for `duration`, see
+    /// [`play_tone`](ScreenContextManager::play_tone).
+    pub fn beep(&mut self, frequency_hz: f32, duration: Duration) -> Result<(), AudioError> {
+        self.play_tone(Waveform::Square, frequency_hz, duration)?;
+        Ok(())
+    }
+
+    /// Loads the WAV file at `path`, converting it to the audio device's format up front so that
+    /// playing it costs nothing more than mixing. Stereo sounds are mixed down to mono.
+    pub fn load_sound<P: AsRef<Path>>(&mut self, path: P) -> Result<Sound, AudioError> {
+        let wav = AudioSpecWAV::load_wav(path)?;
+        let sample_rate = self.speaker()?.device.spec().freq;
+        let conversion = AudioCVT::new(
+            wav.format,
+            wav.channels,
+            wav.freq,
+            AudioFormat::f32_sys(),
+            1,
+            sample_rate,
+        )?;
+        let converted = conversion.convert(wav.buffer().to_vec());
+        let samples = converted
+            .chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        Ok(Sound {
+            samples,
+            sample_rate: sample_rate as u32,
+        })
+    }
+
+    /// Plays `sound` once, on top of anything already playing. Returns right away, the sound is
+    /// mixed on SDL's audio thread.
+    pub fn play_sound(&mut self, sound: &Sound) -> Result<VoiceId, AudioError> {
+        self.play_sound_on_voice(sound, false)
+    }
+
+    /// Plays `sound` over and over until the voice is stopped with
+    /// [`stop_voice`](ScreenContextManager::stop_voice), e.g. for background music.
+    pub fn play_sound_looping(&mut self, sound: &Sound) -> Result<VoiceId, AudioError> {
+        self.play_sound_on_voice(sound, true)
+    }
+
+    fn play_sound_on_voice(&mut self, sound: &Sound, looping: bool) -> Result<VoiceId, AudioError> {
+        let mut mixer = self.speaker()?.device.lock();
+        Ok(mixer.play(Source::Sound(Playback {
+            samples: Arc::clone(&sound.samples),
+            position: 0,
+            looping,
+        })))
+    }
+
+    /// Sets the volume of a voice still playing, from 0 for silence to 1 for the sound's own
+    /// level.
+    pub fn set_voice_volume(&mut self, voice: VoiceId, volume: f32) {
+        if let Some(speaker) = self.speaker.as_mut() {
+            if let Some(voice) = speaker.device.lock().voice(voice) {
+                voice.volume = volume.max(0.0);
+            }
+        }
+    }
+
+    /// Stops a voice right away. Voices that already finished are ignored.
+    pub fn stop_voice(&mut self, voice: VoiceId) {
+        if let Some(speaker) = self.speaker.as_mut() {
+            speaker
+                .device
+                .lock()
+                .voices
+                .retain(|playing| playing.id != voice);
+        }
+    }
+
+    /// Returns whether a voice is still playing.
+    pub fn is_voice_playing(&mut self, voice: VoiceId) -> bool {
+        let speaker = self.speaker.as_mut();
+        speaker.is_some_and(|speaker| speaker.device.lock().voice(voice).is_some())
+    }
+
+    /// Sets how many sounds and tones can play at once, 16 by default. Starting one more stops
+    /// the oldest, see [`play_sound`](ScreenContextManager::play_sound).
+    pub fn set_max_voices(&mut self, voices: usize) -> Result<(), AudioError> {
+        let mut mixer = self.speaker()?.device.lock();
+        mixer.max_voices = voices.max(1);
+        let excess = mixer.voices.len().saturating_sub(mixer.max_voices);
+        mixer.voices.drain(..excess);
+        Ok(())
+    }
+
+    /// Sets the volume everything is played at, from 0 for silence to 1 for full volume.
+    pub fn set_volume(&mut self, volume: f32) -> Result<(), AudioError> {
+        self.speaker()?.device.lock().volume = volume.max(0.0);
+        Ok(())
+    }
+}
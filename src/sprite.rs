@@ -0,0 +1,98 @@
+//! This module provides sprites, images drawn with a position, a scale and flips.
+
+use crate::{DrawCall, Image, LoadImageError, ScreenContextManager};
+use std::path::Path;
+
+/// An image along with where and how to draw it, see
+/// [`draw_sprite`](ScreenContextManager::draw_sprite).
+#[derive(Clone, Debug)]
+pub struct Sprite {
+    pub image: Image,
+    /// Position of the top-left corner on the framebuffer.
+    pub x: i32,
+    pub y: i32,
+    /// Size multiplier, 1 drawing the image at its own size.
+    pub scale: f32,
+    /// Mirrors the image left to right.
+    pub flip_horizontal: bool,
+    /// Mirrors the image top to bottom.
+    pub flip_vertical: bool,
+}
+
+impl Sprite {
+    /// Creates a sprite at (0, 0), unscaled and unflipped.
+    pub fn new(image: Image) -> Sprite {
+        Sprite {
+            image,
+            x: 0,
+            y: 0,
+            scale: 1.0,
+            flip_horizontal: false,
+            flip_vertical: false,
+        }
+    }
+
+    /// Decodes the image at `path` into a new sprite, see [`Image::load`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Sprite, LoadImageError> {
+        Ok(Sprite::new(Image::load(path)?))
+    }
+
+    /// Returns the width and height the sprite covers on the framebuffer.
+    pub fn get_size(&self) -> (u32, u32) {
+        let scaled = |size: u32| (size as f32 * self.scale.max(0.0)).round() as u32;
+        (
+            scaled(self.image.get_width()),
+            scaled(self.image.get_height()),
+        )
+    }
+}
+
+impl ScreenContextManager {
+    /// Draws `sprite` with nearest-neighbor scaling, blending it like
+    /// [`blit_image`](ScreenContextManager::blit_image) does.
+    pub fn draw_sprite(&mut self, sprite: &Sprite) {
+        let (width, height) = sprite.get_size();
+        self.report(|| DrawCall::Sprite {
+            x: sprite.x,
+            y: sprite.y,
+            width,
+            height,
+        });
+        let (x, y, width, height) =
+            self.camera
+                .pixel_rect(sprite.x as i64, sprite.y as i64, width, height);
+        if width == 0 || height == 0 {
+            return;
+        }
+        let (image_width, image_height) = (sprite.image.get_width(), sprite.image.get_height());
+        // Maps a pixel of the drawn sprite back to the image pixel whose area covers its center
+        let source = |offset: u32, size: u32, image_size: u32, flip: bool| {
+            let at = (((offset as f32 + 0.5) * image_size as f32 / size as f32) as u32)
+                .min(image_size - 1);
+            if flip {
+                image_size - 1 - at
+            } else {
+                at
+            }
+        };
+
+        let clip = self.clip_bounds();
+        for row in 0..height {
+            let py = y + row as i64;
+            if !clip.contains_row(py) {
+                continue;
+            }
+            let source_y = source(row, height, image_height, sprite.flip_vertical);
+            for column in 0..width {
+                let px = x + column as i64;
+                if !clip.contains(px, py) {
+                    continue;
+                }
+                let source_x = source(column, width, image_width, sprite.flip_horizontal);
+                let (color, alpha) = sprite.image.sample(source_x, source_y);
+                let i = (py * self.width as i64 + px) as usize;
+                self.blend_image_pixel(i, color, alpha);
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! This module provides off-screen surfaces that drawing can be redirected into, to cache
+//! complex parts of a frame or draw picture-in-picture views.
+
+use crate::{Color, DrawCall, ScreenContextManager};
+
+/// An off-screen buffer of pixels, drawn into by pushing it with
+/// [`push_render_target`](ScreenContextManager::push_render_target) and drawn onto the
+/// framebuffer with [`blit_surface`](ScreenContextManager::blit_surface).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Surface {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl Surface {
+    /// Creates a black surface of the given size.
+    pub fn new(width: u32, height: u32) -> Surface {
+        Surface {
+            width,
+            height,
+            pixels: vec![Color::default(); width as usize * height as usize],
+        }
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the raw RGB24 pixels, row by row.
+    pub fn get_pixels(&self) -> &[u8] {
+        bytemuck::cast_slice(&self.pixels)
+    }
+}
+
+/// What a pushed render target took the place of, put back when it is popped.
+pub(crate) struct SavedTarget {
+    surface: Surface,
+    clip_rect: Option<(u32, u32, u32, u32)>,
+    depth_buffer: Option<Vec<f32>>,
+    front_buffer: Option<Vec<Color>>,
+}
+
+impl ScreenContextManager {
+    /// Redirects all drawing into `surface` until it is popped with
+    /// [`pop_render_target`](ScreenContextManager::pop_render_target), as if the framebuffer
+    /// had the surface's size and contents. The clip rectangle is lifted and, if there is one,
+    /// the target gets a depth buffer of its own. With double buffering the target also gets a
+    /// front buffer of its own, starting out with the surface's contents, which
+    /// [`swap_buffers`](ScreenContextManager::swap_buffers) exchanges with the target. Targets
+    /// can be nested; presenting shows the innermost one, so they should be popped before the
+    /// frame is presented.
+    pub fn push_render_target(&mut self, surface: Surface) {
+        let Surface {
+            width,
+            height,
+            pixels,
+        } = surface;
+        let depth_buffer = self.depth_buffer.take();
+        if depth_buffer.is_some() {
+            self.depth_buffer = Some(vec![f32::INFINITY; pixels.len()]);
+        }
+        let front_buffer = self.front_buffer.take();
+        if front_buffer.is_some() {
+            self.front_buffer = Some(pixels.clone());
+        }
+        let saved = SavedTarget {
+            surface: Surface {
+                width: std::mem::replace(&mut self.width, width),
+                height: std::mem::replace(&mut self.height, height),
+                pixels: std::mem::replace(&mut self.framebuffer, pixels),
+            },
+            clip_rect: self.clip_rect.take(),
+            depth_buffer,
+            front_buffer,
+        };
+        self.render_targets.push(saved);
+    }
+
+    /// Stops drawing into the innermost render target, handing it back with everything that
+    /// was drawn into it, and goes back to drawing where it was before. With double buffering
+    /// that is the target's back buffer, and the target's front buffer is dropped. Returns `None`
+    /// if no target is pushed.
+    pub fn pop_render_target(&mut self) -> Option<Surface> {
+        let saved = self.render_targets.pop()?;
+        self.clip_rect = saved.clip_rect;
+        self.depth_buffer = saved.depth_buffer;
+        self.front_buffer = saved.front_buffer;
+        Some(Surface {
+            width: std::mem::replace(&mut self.width, saved.surface.width),
+            height: std::mem::replace(&mut self.height, saved.surface.height),
+            pixels: std::mem::replace(&mut self.framebuffer, saved.surface.pixels),
+        })
+    }
+
+    /// Copies `surface` onto the framebuffer with its top-left corner at (`x`, `y`), clipping
+    /// the parts that fall outside the clip bounds.
+    pub fn blit_surface(&mut self, surface: &Surface, x: i32, y: i32) {
+        self.report(|| DrawCall::Surface {
+            x,
+            y,
+            width: surface.width,
+            height: surface.height,
+        });
+        if !self.camera.is_identity() {
+            return self.blit_surface_scaled(surface, x, y);
+        }
+        let clip = self.clip_bounds();
+        let left = (x as i64).max(clip.left as i64);
+        let right = (x as i64 + surface.width as i64).min(clip.right as i64);
+        if left >= right {
+            return;
+        }
+        for row in 0..surface.height {
+            let py = y as i64 + row as i64;
+            if !clip.contains_row(py) {
+                continue;
+            }
+            let source = (row * surface.width) as usize;
+            let source =
+                &surface.pixels[source + (left - x as i64) as usize..][..(right - left) as usize];
+            let target = (py * self.width as i64 + left) as usize;
+            self.framebuffer[target..target + source.len()].copy_from_slice(source);
+        }
+    }
+
+    /// Copies `surface` like [`blit_surface`](ScreenContextManager::blit_surface) to the pixels
+    /// it covers through the camera, with nearest-neighbor scaling.
+    fn blit_surface_scaled(&mut self, surface: &Surface, x: i32, y: i32) {
+        let (x, y, width, height) =
+            self.camera
+                .pixel_rect(x as i64, y as i64, surface.width, surface.height);
+        // Maps a pixel of the drawn surface back to the surface pixel whose area covers its center
+        let source = |offset: u32, size: u32, surface_size: u32| {
+            (((offset as f32 + 0.5) * surface_size as f32 / size as f32) as u32)
+                .min(surface_size - 1)
+        };
+        let clip = self.clip_bounds();
+        for row in 0..height {
+            let py = y + row as i64;
+            if !clip.contains_row(py) {
+                continue;
+            }
+            let source_row = source(row, height, surface.height) * surface.width;
+            for column in 0..width {
+                let px = x + column as i64;
+                if clip.contains(px, py) {
+                    let source_column = source(column, width, surface.width);
+                    self.framebuffer[(py * self.width as i64 + px) as usize] =
+                        surface.pixels[(source_row + source_column) as usize];
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,444 @@
+//! This module renders SVG path data (the `d` attribute of `<path>`) with the crate's polygon
+//! filling and stroking primitives.
+
+use crate::primitives::Point;
+use crate::{DrawCall, FillRule, ScreenContextManager, StrokeStyle, SvgPathError};
+
+/// Maximum distance in pixels between a flattened curve and its polyline
+const FLATNESS: f32 = 0.25;
+
+/// A path broken down into polylines, one per subpath.
+struct Subpath {
+    points: Vec<Point>,
+    closed: bool,
+}
+
+impl ScreenContextManager {
+    /// Fills the shape described by SVG path data with the current color, e.g.
+    /// `"M10 10 L50 80 Q 90 10 10 10 Z"`. All subpaths are filled together, so holes follow
+    /// `rule`. Curves and arcs are flattened into short line segments.
+    pub fn fill_svg_path(&mut self, data: &str, rule: FillRule) -> Result<(), SvgPathError> {
+        let subpaths = parse(data)?;
+        self.report(|| DrawCall::SvgPath {
+            data: data.to_string(),
+            filled: true,
+        });
+        let contours: Vec<&[Point]> = subpaths.iter().map(|s| s.points.as_slice()).collect();
+        self.fill_contours(&contours, rule);
+        Ok(())
+    }
+
+    /// Strokes the outline described by SVG path data with the current color and `style`.
+    pub fn stroke_svg_path(&mut self, data: &str, style: &StrokeStyle) -> Result<(), SvgPathError> {
+        let subpaths = parse(data)?;
+        self.report(|| DrawCall::SvgPath {
+            data: data.to_string(),
+            filled: false,
+        });
+        for subpath in subpaths {
+            self.stroke(&subpath.points, style, subpath.closed);
+        }
+        Ok(())
+    }
+}
+
+/// Splits path data into command letters and the numbers following them.
+struct Tokens<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn skip_separators(&mut self) {
+        while self.position < self.data.len()
+            && (self.data[self.position].is_ascii_whitespace() || self.data[self.position] == b',')
+        {
+            self.position += 1;
+        }
+    }
+
+    /// Returns the next command letter, if the next token is one.
+    fn command(&mut self) -> Option<char> {
+        self.skip_separators();
+        let c = *self.data.get(self.position)? as char;
+        if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            self.position += 1;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    fn at_number(&mut self) -> bool {
+        self.skip_separators();
+        matches!(self.data.get(self.position), Some(c) if c.is_ascii_digit() || b"+-.".contains(c))
+    }
+
+    fn number(&mut self, command: char) -> Result<f32, SvgPathError> {
+        if !self.at_number() {
+            return Err(SvgPathError::MissingNumbers(command));
+        }
+
+        let start = self.position;
+        let mut seen_dot = false;
+        let mut seen_exponent = false;
+        if b"+-".contains(&self.data[self.position]) {
+            self.position += 1;
+        }
+        while let Some(&c) = self.data.get(self.position) {
+            match c {
+                b'0'..=b'9' => {}
+                // A second dot starts the next number, as in "0.5.5"
+                b'.' if !seen_dot && !seen_exponent => seen_dot = true,
+                b'e' | b'E' if !seen_exponent => {
+                    seen_exponent = true;
+                    if matches!(self.data.get(self.position + 1), Some(b'+') | Some(b'-')) {
+                        self.position += 1;
+                    }
+                }
+                _ => break,
+            }
+            self.position += 1;
+        }
+
+        std::str::from_utf8(&self.data[start..self.position])
+            .ok()
+            .and_then(|text| text.parse().ok())
+            .ok_or(SvgPathError::MissingNumbers(command))
+    }
+
+    /// Arc flags are single digits that may be written without separators, as in "a1 1 0 01 5 5".
+    fn flag(&mut self, command: char) -> Result<bool, SvgPathError> {
+        self.skip_separators();
+        let flag = match self.data.get(self.position) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(SvgPathError::MissingNumbers(command)),
+        };
+        self.position += 1;
+        Ok(flag)
+    }
+}
+
+fn parse(data: &str) -> Result<Vec<Subpath>, SvgPathError> {
+    let mut tokens = Tokens {
+        data: data.as_bytes(),
+        position: 0,
+    };
+    let mut subpaths: Vec<Subpath> = Vec::new();
+    let mut current: Point = (0.0, 0.0);
+    let mut start: Point = (0.0, 0.0);
+    // Control point of the previous curve, reflected by the smooth curve commands
+    let mut last_control: Option<(char, Point)> = None;
+    let mut command = match tokens.command() {
+        Some(c) if c == 'M' || c == 'm' => c,
+        Some(c) => return Err(SvgPathError::UnexpectedCommand(c)),
+        None if tokens.at_number() || tokens.position < tokens.data.len() => {
+            let c = tokens.data[tokens.position] as char;
+            return Err(SvgPathError::UnexpectedCharacter(c));
+        }
+        None => return Ok(subpaths),
+    };
+
+    loop {
+        let kind = command.to_ascii_uppercase();
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative { current } else { (0.0, 0.0) };
+        let offset = |p: Point| (p.0 + origin.0, p.1 + origin.1);
+        let mut control = None;
+
+        match kind {
+            'M' => {
+                current = offset((tokens.number(command)?, tokens.number(command)?));
+                start = current;
+                subpaths.push(Subpath {
+                    points: vec![current],
+                    closed: false,
+                });
+            }
+            'L' => current = offset((tokens.number(command)?, tokens.number(command)?)),
+            'H' => current.0 = tokens.number(command)? + origin.0,
+            'V' => current.1 = tokens.number(command)? + origin.1,
+            'C' | 'S' => {
+                let control_1 = if kind == 'C' {
+                    offset((tokens.number(command)?, tokens.number(command)?))
+                } else {
+                    reflect(last_control, current, "CS")
+                };
+                let control_2 = offset((tokens.number(command)?, tokens.number(command)?));
+                let end = offset((tokens.number(command)?, tokens.number(command)?));
+                let points = current_points(&mut subpaths, current);
+                flatten_cubic(points, current, control_1, control_2, end);
+                current = end;
+                control = Some(('C', control_2));
+            }
+            'Q' | 'T' => {
+                let control_1 = if kind == 'Q' {
+                    offset((tokens.number(command)?, tokens.number(command)?))
+                } else {
+                    reflect(last_control, current, "QT")
+                };
+                let end = offset((tokens.number(command)?, tokens.number(command)?));
+                // A quadratic curve is a cubic one with both controls 2/3 of the way there
+                let lerp = |a: Point, b: Point| {
+                    (a.0 + (b.0 - a.0) * 2.0 / 3.0, a.1 + (b.1 - a.1) * 2.0 / 3.0)
+                };
+                let points = current_points(&mut subpaths, current);
+                flatten_cubic(
+                    points,
+                    current,
+                    lerp(current, control_1),
+                    lerp(end, control_1),
+                    end,
+                );
+                current = end;
+                control = Some(('Q', control_1));
+            }
+            'A' => {
+                let radius_x = tokens.number(command)?.abs();
+                let radius_y = tokens.number(command)?.abs();
+                let rotation = tokens.number(command)?.to_radians();
+                let large_arc = tokens.flag(command)?;
+                let sweep = tokens.flag(command)?;
+                let end = offset((tokens.number(command)?, tokens.number(command)?));
+                let points = current_points(&mut subpaths, current);
+                flatten_arc(
+                    points,
+                    current,
+                    (radius_x, radius_y),
+                    rotation,
+                    large_arc,
+                    sweep,
+                    end,
+                );
+                current = end;
+            }
+            'Z' => {
+                if let Some(subpath) = subpaths.last_mut() {
+                    subpath.closed = true;
+                }
+                current = start;
+            }
+            _ => return Err(SvgPathError::UnexpectedCommand(command)),
+        }
+        last_control = control;
+
+        if matches!(kind, 'L' | 'H' | 'V') {
+            current_points(&mut subpaths, current).push(current);
+        }
+        if kind == 'M' {
+            // Further coordinate pairs are implicit line commands
+            command = if relative { 'l' } else { 'L' };
+        }
+
+        // Commands repeat for as long as numbers follow them
+        if kind == 'Z' || !tokens.at_number() {
+            command = match tokens.command() {
+                Some(c) => c,
+                None if tokens.position < tokens.data.len() => {
+                    let c = tokens.data[tokens.position] as char;
+                    return Err(SvgPathError::UnexpectedCharacter(c));
+                }
+                None => break,
+            };
+            // Drawing after a close starts a new subpath at the same point
+            if !matches!(command, 'M' | 'm') && subpaths.last().is_some_and(|s| s.closed) {
+                subpaths.push(Subpath {
+                    points: vec![current],
+                    closed: false,
+                });
+            }
+        }
+    }
+
+    Ok(subpaths)
+}
+
+/// Returns the points of the subpath being drawn, starting one at `current` if there is none.
+fn current_points(subpaths: &mut Vec<Subpath>, current: Point) -> &mut Vec<Point> {
+    if subpaths.is_empty() {
+        subpaths.push(Subpath {
+            points: vec![current],
+            closed: false,
+        });
+    }
+    &mut subpaths.last_mut().expect("just ensured a subpath").points
+}
+
+/// The first control point of a smooth curve: the previous curve's last control point mirrored
+/// around `current`, or `current` itself if the previous command wasn't one of `kinds`.
+fn reflect(last_control: Option<(char, Point)>, current: Point, kinds: &str) -> Point {
+    match last_control {
+        Some((kind, control)) if kinds.starts_with(kind) => {
+            (2.0 * current.0 - control.0, 2.0 * current.1 - control.1)
+        }
+        _ => current,
+    }
+}
+
+/// Appends a cubic Bézier curve to `points` as line segments, the first point being left out.
+fn flatten_cubic(points: &mut Vec<Point>, p0: Point, p1: Point, p2: Point, p3: Point) {
+    let distance = |a: Point, b: Point| ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+    // The control polygon is never shorter than the curve
+    let length = distance(p0, p1) + distance(p1, p2) + distance(p2, p3);
+    let steps = ((length / FLATNESS).sqrt().ceil() as usize).clamp(1, 256);
+
+    for step in 1..=steps {
+        let t = step as f32 / steps as f32;
+        let u = 1.0 - t;
+        let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+        points.push((
+            a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+            a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+        ));
+    }
+}
+
+/// Appends an elliptical arc to `points` as line segments, following the endpoint
+/// parameterization of the SVG specification (appendix B.2.4).
+fn flatten_arc(
+    points: &mut Vec<Point>,
+    from: Point,
+    radii: Point,
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: Point,
+) {
+    let (mut rx, mut ry) = radii;
+    if rx == 0.0 || ry == 0.0 || from == to {
+        points.push(to);
+        return;
+    }
+
+    let (sin, cos) = rotation.sin_cos();
+    let half_dx = (from.0 - to.0) / 2.0;
+    let half_dy = (from.1 - to.1) / 2.0;
+    let x1 = cos * half_dx + sin * half_dy;
+    let y1 = -sin * half_dx + cos * half_dy;
+
+    // Radii too small to reach the end point get scaled up just enough
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut factor = (numerator / denominator).max(0.0).sqrt();
+    if large_arc == sweep {
+        factor = -factor;
+    }
+    let center_x1 = factor * rx * y1 / ry;
+    let center_y1 = -factor * ry * x1 / rx;
+    let center = (
+        cos * center_x1 - sin * center_y1 + (from.0 + to.0) / 2.0,
+        sin * center_x1 + cos * center_y1 + (from.1 + to.1) / 2.0,
+    );
+
+    let angle = |ux: f32, uy: f32| uy.atan2(ux);
+    let start_angle = angle((x1 - center_x1) / rx, (y1 - center_y1) / ry);
+    let mut sweep_angle = angle((-x1 - center_x1) / rx, (-y1 - center_y1) / ry) - start_angle;
+    if sweep && sweep_angle < 0.0 {
+        sweep_angle += std::f32::consts::TAU;
+    } else if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= std::f32::consts::TAU;
+    }
+
+    let arc_length = sweep_angle.abs() * rx.max(ry);
+    let steps = ((arc_length / FLATNESS).sqrt().ceil() as usize).clamp(1, 256);
+    for step in 1..=steps {
+        let theta = start_angle + sweep_angle * step as f32 / steps as f32;
+        let (x, y) = (rx * theta.cos(), ry * theta.sin());
+        points.push((cos * x - sin * y + center.0, sin * x + cos * y + center.1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Point, b: Point) -> bool {
+        (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3
+    }
+
+    #[test]
+    fn lines_absolute_and_relative() {
+        let subpaths = parse("M10,10 L20 10 h5 v-5 l-15 0 z").unwrap();
+        assert_eq!(subpaths.len(), 1);
+        let points = &subpaths[0].points;
+        let expected = [
+            (10.0, 10.0),
+            (20.0, 10.0),
+            (25.0, 10.0),
+            (25.0, 5.0),
+            (10.0, 5.0),
+        ];
+        assert_eq!(points.as_slice(), expected);
+        assert!(subpaths[0].closed);
+    }
+
+    #[test]
+    fn move_pairs_continue_as_lines() {
+        let subpaths = parse("m1 1 2 0 0 2").unwrap();
+        assert_eq!(subpaths[0].points, [(1.0, 1.0), (3.0, 1.0), (3.0, 3.0)]);
+    }
+
+    #[test]
+    fn numbers_without_separators() {
+        let subpaths = parse("M0.5.5L1e1-2E-1").unwrap();
+        assert_eq!(subpaths[0].points, [(0.5, 0.5), (10.0, -0.2)]);
+    }
+
+    #[test]
+    fn curves_end_on_their_end_point() {
+        let subpaths = parse("M0 0 C0 10 10 10 10 0 S20 -10 20 0 Q25 5 30 0 T40 0").unwrap();
+        let points = &subpaths[0].points;
+        assert!(points.len() > 8);
+        for end in [(10.0, 0.0), (20.0, 0.0), (30.0, 0.0), (40.0, 0.0)] {
+            assert!(points.iter().any(|&p| close(p, end)));
+        }
+        assert!(close(*points.last().unwrap(), (40.0, 0.0)));
+    }
+
+    #[test]
+    fn arcs_stay_on_their_circle() {
+        let subpaths = parse("M0 0 a10 10 0 01 20 0").unwrap();
+        let points = &subpaths[0].points;
+        assert!(close(*points.last().unwrap(), (20.0, 0.0)));
+        for &(x, y) in points {
+            let distance = ((x - 10.0).powi(2) + y * y).sqrt();
+            assert!((distance - 10.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn drawing_after_a_close_starts_a_new_subpath() {
+        let subpaths = parse("M0 0 L5 0 5 5 Z L0 5").unwrap();
+        assert_eq!(subpaths.len(), 2);
+        assert_eq!(subpaths[1].points, [(0.0, 0.0), (0.0, 5.0)]);
+        assert!(!subpaths[1].closed);
+    }
+
+    #[test]
+    fn malformed_data_is_rejected() {
+        assert!(parse("").unwrap().is_empty());
+        assert!(matches!(
+            parse("L1 1"),
+            Err(SvgPathError::UnexpectedCommand('L'))
+        ));
+        assert!(matches!(
+            parse("M1 1 L2"),
+            Err(SvgPathError::MissingNumbers('L'))
+        ));
+        assert!(matches!(
+            parse("M1 1 #"),
+            Err(SvgPathError::UnexpectedCharacter('#'))
+        ));
+        assert!(matches!(
+            parse("M1 1 X2 2"),
+            Err(SvgPathError::UnexpectedCommand('X'))
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+//! This module renders text with a built-in 8×8 bitmap font straight into the framebuffer.
+
+use crate::{clip::ClipRect, Color, DrawCall, Image, ScreenContextManager};
+
+/// Width and height of every glyph of the built-in font, spacing included.
+pub const GLYPH_SIZE: u32 = 8;
+
+/// Glyphs of the printable ASCII characters from `' '` to `'~'`, one byte per row with the most
+/// significant bit on the left. Taken from the 8×8 font of SDL2_gfx, zlib licensed,
+/// (c) A. Schiffler 2012.
+const FONT_8X8: [[u8; 8]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x18, 0x3c, 0x3c, 0x18, 0x18, 0x00, 0x18, 0x00], // '!'
+    [0x66, 0x66, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x6c, 0x6c, 0xfe, 0x6c, 0xfe, 0x6c, 0x6c, 0x00], // '#'
+    [0x18, 0x3e, 0x60, 0x3c, 0x06, 0x7c, 0x18, 0x00], // '$'
+    [0x00, 0xc6, 0xcc, 0x18, 0x30, 0x66, 0xc6, 0x00], // '%'
+    [0x38, 0x6c, 0x38, 0x76, 0xdc, 0xcc, 0x76, 0x00], // '&'
+    [0x18, 0x18, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00], // "'"
+    [0x0c, 0x18, 0x30, 0x30, 0x30, 0x18, 0x0c, 0x00], // '('
+    [0x30, 0x18, 0x0c, 0x0c, 0x0c, 0x18, 0x30, 0x00], // ')'
+    [0x00, 0x66, 0x3c, 0xff, 0x3c, 0x66, 0x00, 0x00], // '*'
+    [0x00, 0x18, 0x18, 0x7e, 0x18, 0x18, 0x00, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x30], // ','
+    [0x00, 0x00, 0x00, 0x7e, 0x00, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00], // '.'
+    [0x06, 0x0c, 0x18, 0x30, 0x60, 0xc0, 0x80, 0x00], // '/'
+    [0x38, 0x6c, 0xc6, 0xd6, 0xc6, 0x6c, 0x38, 0x00], // '0'
+    [0x18, 0x38, 0x18, 0x18, 0x18, 0x18, 0x7e, 0x00], // '1'
+    [0x7c, 0xc6, 0x06, 0x1c, 0x30, 0x66, 0xfe, 0x00], // '2'
+    [0x7c, 0xc6, 0x06, 0x3c, 0x06, 0xc6, 0x7c, 0x00], // '3'
+    [0x1c, 0x3c, 0x6c, 0xcc, 0xfe, 0x0c, 0x1e, 0x00], // '4'
+    [0xfe, 0xc0, 0xc0, 0xfc, 0x06, 0xc6, 0x7c, 0x00], // '5'
+    [0x38, 0x60, 0xc0, 0xfc, 0xc6, 0xc6, 0x7c, 0x00], // '6'
+    [0xfe, 0xc6, 0x0c, 0x18, 0x30, 0x30, 0x30, 0x00], // '7'
+    [0x7c, 0xc6, 0xc6, 0x7c, 0xc6, 0xc6, 0x7c, 0x00], // '8'
+    [0x7c, 0xc6, 0xc6, 0x7e, 0x06, 0x0c, 0x78, 0x00], // '9'
+    [0x00, 0x18, 0x18, 0x00, 0x00, 0x18, 0x18, 0x00], // ':'
+    [0x00, 0x18, 0x18, 0x00, 0x00, 0x18, 0x18, 0x30], // ';'
+    [0x06, 0x0c, 0x18, 0x30, 0x18, 0x0c, 0x06, 0x00], // '<'
+    [0x00, 0x00, 0x7e, 0x00, 0x00, 0x7e, 0x00, 0x00], // '='
+    [0x60, 0x30, 0x18, 0x0c, 0x18, 0x30, 0x60, 0x00], // '>'
+    [0x7c, 0xc6, 0x0c, 0x18, 0x18, 0x00, 0x18, 0x00], // '?'
+    [0x7c, 0xc6, 0xde, 0xde, 0xde, 0xc0, 0x78, 0x00], // '@'
+    [0x38, 0x6c, 0xc6, 0xfe, 0xc6, 0xc6, 0xc6, 0x00], // 'A'
+    [0xfc, 0x66, 0x66, 0x7c, 0x66, 0x66, 0xfc, 0x00], // 'B'
+    [0x3c, 0x66, 0xc0, 0xc0, 0xc0, 0x66, 0x3c, 0x00], // 'C'
+    [0xf8, 0x6c, 0x66, 0x66, 0x66, 0x6c, 0xf8, 0x00], // 'D'
+    [0xfe, 0x62, 0x68, 0x78, 0x68, 0x62, 0xfe, 0x00], // 'E'
+    [0xfe, 0x62, 0x68, 0x78, 0x68, 0x60, 0xf0, 0x00], // 'F'
+    [0x3c, 0x66, 0xc0, 0xc0, 0xce, 0x66, 0x3a, 0x00], // 'G'
+    [0xc6, 0xc6, 0xc6, 0xfe, 0xc6, 0xc6, 0xc6, 0x00], // 'H'
+    [0x3c, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3c, 0x00], // 'I'
+    [0x1e, 0x0c, 0x0c, 0x0c, 0xcc, 0xcc, 0x78, 0x00], // 'J'
+    [0xe6, 0x66, 0x6c, 0x78, 0x6c, 0x66, 0xe6, 0x00], // 'K'
+    [0xf0, 0x60, 0x60, 0x60, 0x62, 0x66, 0xfe, 0x00], // 'L'
+    [0xc6, 0xee, 0xfe, 0xfe, 0xd6, 0xc6, 0xc6, 0x00], // 'M'
+    [0xc6, 0xe6, 0xf6, 0xde, 0xce, 0xc6, 0xc6, 0x00], // 'N'
+    [0x7c, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00], // 'O'
+    [0xfc, 0x66, 0x66, 0x7c, 0x60, 0x60, 0xf0, 0x00], // 'P'
+    [0x7c, 0xc6, 0xc6, 0xc6, 0xc6, 0xce, 0x7c, 0x0e], // 'Q'
+    [0xfc, 0x66, 0x66, 0x7c, 0x6c, 0x66, 0xe6, 0x00], // 'R'
+    [0x3c, 0x66, 0x30, 0x18, 0x0c, 0x66, 0x3c, 0x00], // 'S'
+    [0x7e, 0x7e, 0x5a, 0x18, 0x18, 0x18, 0x3c, 0x00], // 'T'
+    [0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00], // 'U'
+    [0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x6c, 0x38, 0x00], // 'V'
+    [0xc6, 0xc6, 0xc6, 0xd6, 0xd6, 0xfe, 0x6c, 0x00], // 'W'
+    [0xc6, 0xc6, 0x6c, 0x38, 0x6c, 0xc6, 0xc6, 0x00], // 'X'
+    [0x66, 0x66, 0x66, 0x3c, 0x18, 0x18, 0x3c, 0x00], // 'Y'
+    [0xfe, 0xc6, 0x8c, 0x18, 0x32, 0x66, 0xfe, 0x00], // 'Z'
+    [0x3c, 0x30, 0x30, 0x30, 0x30, 0x30, 0x3c, 0x00], // '['
+    [0xc0, 0x60, 0x30, 0x18, 0x0c, 0x06, 0x02, 0x00], // '\\'
+    [0x3c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x3c, 0x00], // ']'
+    [0x10, 0x38, 0x6c, 0xc6, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff], // '_'
+    [0x30, 0x18, 0x0c, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x78, 0x0c, 0x7c, 0xcc, 0x76, 0x00], // 'a'
+    [0xe0, 0x60, 0x7c, 0x66, 0x66, 0x66, 0xdc, 0x00], // 'b'
+    [0x00, 0x00, 0x7c, 0xc6, 0xc0, 0xc6, 0x7c, 0x00], // 'c'
+    [0x1c, 0x0c, 0x7c, 0xcc, 0xcc, 0xcc, 0x76, 0x00], // 'd'
+    [0x00, 0x00, 0x7c, 0xc6, 0xfe, 0xc0, 0x7c, 0x00], // 'e'
+    [0x3c, 0x66, 0x60, 0xf8, 0x60, 0x60, 0xf0, 0x00], // 'f'
+    [0x00, 0x00, 0x76, 0xcc, 0xcc, 0x7c, 0x0c, 0xf8], // 'g'
+    [0xe0, 0x60, 0x6c, 0x76, 0x66, 0x66, 0xe6, 0x00], // 'h'
+    [0x18, 0x00, 0x38, 0x18, 0x18, 0x18, 0x3c, 0x00], // 'i'
+    [0x06, 0x00, 0x06, 0x06, 0x06, 0x66, 0x66, 0x3c], // 'j'
+    [0xe0, 0x60, 0x66, 0x6c, 0x78, 0x6c, 0xe6, 0x00], // 'k'
+    [0x38, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3c, 0x00], // 'l'
+    [0x00, 0x00, 0xec, 0xfe, 0xd6, 0xd6, 0xd6, 0x00], // 'm'
+    [0x00, 0x00, 0xdc, 0x66, 0x66, 0x66, 0x66, 0x00], // 'n'
+    [0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xc6, 0x7c, 0x00], // 'o'
+    [0x00, 0x00, 0xdc, 0x66, 0x66, 0x7c, 0x60, 0xf0], // 'p'
+    [0x00, 0x00, 0x76, 0xcc, 0xcc, 0x7c, 0x0c, 0x1e], // 'q'
+    [0x00, 0x00, 0xdc, 0x76, 0x60, 0x60, 0xf0, 0x00], // 'r'
+    [0x00, 0x00, 0x7e, 0xc0, 0x7c, 0x06, 0xfc, 0x00], // 's'
+    [0x30, 0x30, 0xfc, 0x30, 0x30, 0x36, 0x1c, 0x00], // 't'
+    [0x00, 0x00, 0xcc, 0xcc, 0xcc, 0xcc, 0x76, 0x00], // 'u'
+    [0x00, 0x00, 0xc6, 0xc6, 0xc6, 0x6c, 0x38, 0x00], // 'v'
+    [0x00, 0x00, 0xc6, 0xd6, 0xd6, 0xfe, 0x6c, 0x00], // 'w'
+    [0x00, 0x00, 0xc6, 0x6c, 0x38, 0x6c, 0xc6, 0x00], // 'x'
+    [0x00, 0x00, 0xc6, 0xc6, 0xc6, 0x7e, 0x06, 0xfc], // 'y'
+    [0x00, 0x00, 0x7e, 0x4c, 0x18, 0x32, 0x7e, 0x00], // 'z'
+    [0x0e, 0x18, 0x18, 0x70, 0x18, 0x18, 0x0e, 0x00], // '{'
+    [0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00], // '|'
+    [0x70, 0x18, 0x18, 0x0e, 0x18, 0x18, 0x70, 0x00], // '}'
+    [0x76, 0xdc, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '~'
+];
+
+/// Returns the glyph for `c`, with a question mark standing in for characters the font lacks.
+fn glyph(c: char) -> &'static [u8; 8] {
+    let index = match c {
+        ' '..='~' => c as usize - ' ' as usize,
+        _ => '?' as usize - ' ' as usize,
+    };
+    &FONT_8X8[index]
+}
+
+/// Draws `text` with the built-in font in `color` into a buffer `width` pixels wide, its top-left
+/// corner at (`x`, `y`), clipping anything outside `clip`.
+pub fn draw_text(
+    pixels: &mut [Color],
+    width: u32,
+    clip: ClipRect,
+    (x, y): (i32, i32),
+    text: &str,
+    color: Color,
+) {
+    for (dx, dy) in text_pixels(text) {
+        let (px, py) = (x as i64 + dx as i64, y as i64 + dy as i64);
+        if clip.contains(px, py) {
+            pixels[(py * width as i64 + px) as usize] = color;
+        }
+    }
+}
+
+/// Returns the set pixels of the glyphs of `text`, relative to its top-left corner.
+fn text_pixels(text: &str) -> impl Iterator<Item = (u32, u32)> + '_ {
+    text.split('\n').enumerate().flat_map(|(line_index, line)| {
+        let top = line_index as u32 * GLYPH_SIZE;
+        line.chars().enumerate().flat_map(move |(column, c)| {
+            let left = column as u32 * GLYPH_SIZE;
+            glyph(c).iter().enumerate().flat_map(move |(dy, &bits)| {
+                (0..GLYPH_SIZE)
+                    .filter(move |dx| bits & (0x80 >> dx) != 0)
+                    .map(move |dx| (left + dx, top + dy as u32))
+            })
+        })
+    })
+}
+
+/// Returns the width and height in pixels that `text` covers with the built-in font.
+pub fn measure_text(text: &str) -> (u32, u32) {
+    let lines = text.split('\n');
+    let columns = lines.clone().map(|line| line.chars().count()).max();
+    (
+        columns.unwrap_or(0) as u32 * GLYPH_SIZE,
+        lines.count() as u32 * GLYPH_SIZE,
+    )
+}
+
+impl ScreenContextManager {
+    /// Draws `text` with the built-in 8×8 font in the current color, its top-left corner at
+    /// (`x`, `y`). Every `\n` starts a new line below the first one. Only the set pixels of the
+    /// glyphs are drawn, and anything outside the framebuffer or the clip rectangle is clipped.
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str) {
+        self.report(|| DrawCall::Text {
+            x,
+            y,
+            text: text.to_string(),
+        });
+        if self.camera.is_identity() {
+            let (width, clip) = (self.width, self.clip_bounds());
+            return draw_text(&mut self.framebuffer, width, clip, (x, y), text, self.color);
+        }
+        // Drawn through an image so that the glyphs are scaled like everything else
+        let (width, height) = measure_text(text);
+        let mut pixels = image::RgbaImage::new(width, height);
+        let Color { r, g, b } = self.color;
+        for (x, y) in text_pixels(text) {
+            pixels.put_pixel(x, y, image::Rgba([r, g, b, u8::MAX]));
+        }
+        self.blit_image(&Image::from_pixels(pixels), x, y);
+    }
+
+    /// Returns the width and height in pixels that [`draw_text`](Self::draw_text) covers when
+    /// drawing `text`, to center, align or wrap it.
+    pub fn measure_text(&self, text: &str) -> (u32, u32) {
+        measure_text(text)
+    }
+}
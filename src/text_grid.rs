@@ -0,0 +1,247 @@
+//! This module provides a terminal-like grid of characters with a cursor and scrollback, drawn
+//! with the built-in font.
+
+use crate::text::GLYPH_SIZE;
+use crate::ScreenContextManager;
+use std::collections::VecDeque;
+
+/// A fixed grid of `columns`×`rows` characters that text is written into like a terminal:
+/// writing wraps at the right edge, and lines scrolled off the top are kept as scrollback. Drawn
+/// with [`draw_text_grid`](ScreenContextManager::draw_text_grid).
+#[derive(Clone, Debug)]
+pub struct TextGrid {
+    columns: u32,
+    rows: u32,
+    /// The scrollback followed by the `rows` lines of the grid, each `columns` long
+    lines: VecDeque<Vec<char>>,
+    scrollback: usize,
+    /// How many lines the view is scrolled back
+    view_offset: usize,
+    cursor: (u32, u32),
+    cursor_visible: bool,
+}
+
+impl TextGrid {
+    /// Creates an empty grid with the cursor in the top-left cell, keeping up to 100 lines of
+    /// scrollback.
+    pub fn new(columns: u32, rows: u32) -> TextGrid {
+        let mut grid = TextGrid {
+            columns,
+            rows,
+            lines: VecDeque::new(),
+            scrollback: 100,
+            view_offset: 0,
+            cursor: (0, 0),
+            cursor_visible: true,
+        };
+        grid.clear();
+        grid
+    }
+
+    /// Returns the number of columns and rows of the grid.
+    pub fn get_size(&self) -> (u32, u32) {
+        (self.columns, self.rows)
+    }
+
+    /// Sets how many lines scrolled off the top are kept, dropping the oldest ones.
+    pub fn set_scrollback(&mut self, lines: usize) {
+        self.scrollback = lines;
+        self.trim_scrollback();
+    }
+
+    /// Empties the grid and its scrollback and puts the cursor in the top-left cell.
+    pub fn clear(&mut self) {
+        let blank = vec![' '; self.columns as usize];
+        self.lines = std::iter::repeat_n(blank, self.rows as usize).collect();
+        self.view_offset = 0;
+        self.cursor = (0, 0);
+    }
+
+    /// Writes `text` at the cursor, moving it along. `\n` moves to the start of the next line
+    /// and `\r` to the start of the current one, while `\u{8}` (backspace) steps back one cell.
+    /// Writing past the bottom row scrolls the grid up, and the view jumps back to the bottom.
+    pub fn write_str(&mut self, text: &str) {
+        if self.columns == 0 || self.rows == 0 {
+            return;
+        }
+        self.view_offset = 0;
+        for c in text.chars() {
+            match c {
+                '\n' => self.new_line(),
+                '\r' => self.cursor.0 = 0,
+                '\u{8}' => self.cursor.0 = self.cursor.0.saturating_sub(1),
+                c => {
+                    if self.cursor.0 == self.columns {
+                        self.new_line();
+                    }
+                    let (column, row) = self.cursor;
+                    let line = self.lines.len() - self.rows as usize + row as usize;
+                    self.lines[line][column as usize] = c;
+                    self.cursor.0 += 1;
+                }
+            }
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.cursor.0 = 0;
+        if self.cursor.1 + 1 < self.rows {
+            self.cursor.1 += 1;
+        } else {
+            self.lines.push_back(vec![' '; self.columns as usize]);
+            self.trim_scrollback();
+        }
+    }
+
+    fn trim_scrollback(&mut self) {
+        let kept = self.rows as usize + self.scrollback;
+        while self.lines.len() > kept {
+            self.lines.pop_front();
+        }
+        self.view_offset = self.view_offset.min(self.lines.len() - self.rows as usize);
+    }
+
+    /// Moves the cursor to the cell at (`column`, `row`), clamped to the grid.
+    pub fn set_cursor(&mut self, column: u32, row: u32) {
+        self.cursor = (
+            column.min(self.columns.saturating_sub(1)),
+            row.min(self.rows.saturating_sub(1)),
+        );
+    }
+
+    /// Returns the cell the cursor is in as (`column`, `row`).
+    pub fn get_cursor(&self) -> (u32, u32) {
+        (
+            self.cursor.0.min(self.columns.saturating_sub(1)),
+            self.cursor.1,
+        )
+    }
+
+    /// Shows or hides the cursor, which is drawn as an underline.
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        self.cursor_visible = visible;
+    }
+
+    /// Scrolls the view back into the scrollback by `lines`, or towards the bottom for negative
+    /// amounts, stopping at either end.
+    pub fn scroll(&mut self, lines: i32) {
+        let limit = self.lines.len() - self.rows as usize;
+        let offset = self.view_offset as i64 + lines as i64;
+        self.view_offset = offset.clamp(0, limit as i64) as usize;
+    }
+
+    /// Returns the text of the given row of the view, without trailing spaces.
+    pub fn get_row(&self, row: u32) -> Option<String> {
+        let line = self.visible_lines().nth(row as usize)?;
+        Some(line.iter().collect::<String>().trim_end().to_string())
+    }
+
+    fn visible_lines(&self) -> impl Iterator<Item = &Vec<char>> {
+        let first = self.lines.len() - self.rows as usize - self.view_offset;
+        self.lines.range(first..first + self.rows as usize)
+    }
+}
+
+impl std::fmt::Write for TextGrid {
+    fn write_str(&mut self, text: &str) -> std::fmt::Result {
+        TextGrid::write_str(self, text);
+        Ok(())
+    }
+}
+
+impl ScreenContextManager {
+    /// Draws the visible rows of `grid` in the current color with the top-left cell at
+    /// (`x`, `y`), every cell being 8×8 pixels. Only the characters are drawn, so the background
+    /// shows through. The cursor is only shown while the view isn't scrolled back.
+    pub fn draw_text_grid(&mut self, grid: &TextGrid, x: i32, y: i32) {
+        for (row, line) in grid.visible_lines().enumerate() {
+            let line: String = line.iter().collect();
+            let top = y + (row as u32 * GLYPH_SIZE) as i32;
+            self.draw_text(x, top, &line);
+        }
+
+        if grid.cursor_visible && grid.view_offset == 0 && grid.columns > 0 && grid.rows > 0 {
+            let (column, row) = grid.get_cursor();
+            let left = x as i64 + (column * GLYPH_SIZE) as i64;
+            let bottom = y as i64 + ((row + 1) * GLYPH_SIZE) as i64 - 1;
+            // The underline goes through the camera like the text above it
+            let (left, bottom, width, height) = self.camera.pixel_rect(left, bottom, GLYPH_SIZE, 1);
+            let clip = self.clip_bounds();
+            for py in bottom..bottom + height as i64 {
+                for px in left..left + width as i64 {
+                    if clip.contains(px, py) {
+                        self.framebuffer[(py * self.width as i64 + px) as usize] = self.color;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(grid: &TextGrid) -> Vec<String> {
+        (0..grid.get_size().1)
+            .map(|row| grid.get_row(row).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn writing_wraps_at_the_right_edge() {
+        let mut grid = TextGrid::new(4, 3);
+        grid.write_str("abcdef");
+        assert_eq!(rows(&grid), ["abcd", "ef", ""]);
+        assert_eq!(grid.get_cursor(), (2, 1));
+    }
+
+    #[test]
+    fn cursor_stays_on_a_full_line_until_the_next_character() {
+        let mut grid = TextGrid::new(4, 2);
+        grid.write_str("abcd");
+        assert_eq!(grid.get_cursor(), (3, 0));
+        grid.write_str("e");
+        assert_eq!(grid.get_cursor(), (1, 1));
+    }
+
+    #[test]
+    fn control_characters_move_the_cursor() {
+        let mut grid = TextGrid::new(8, 2);
+        grid.write_str("hello\rj\u{8}J\nok");
+        assert_eq!(rows(&grid), ["Jello", "ok"]);
+        assert_eq!(grid.get_cursor(), (2, 1));
+    }
+
+    #[test]
+    fn scrolled_off_lines_are_kept_as_scrollback() {
+        let mut grid = TextGrid::new(4, 2);
+        grid.set_scrollback(1);
+        grid.write_str("1\n2\n3\n4");
+        assert_eq!(rows(&grid), ["3", "4"]);
+        grid.scroll(5);
+        assert_eq!(rows(&grid), ["2", "3"]);
+        grid.scroll(-1);
+        assert_eq!(rows(&grid), ["3", "4"]);
+        grid.scroll(1);
+        grid.write_str("!");
+        assert_eq!(rows(&grid), ["3", "4!"]);
+    }
+
+    #[test]
+    fn cursor_is_clamped_to_the_grid() {
+        let mut grid = TextGrid::new(4, 2);
+        grid.set_cursor(10, 10);
+        assert_eq!(grid.get_cursor(), (3, 1));
+        grid.clear();
+        assert_eq!(grid.get_cursor(), (0, 0));
+    }
+
+    #[test]
+    fn empty_grids_ignore_text() {
+        let mut grid = TextGrid::new(0, 0);
+        grid.write_str("text");
+        grid.scroll(3);
+        assert_eq!(grid.get_row(0), None);
+    }
+}
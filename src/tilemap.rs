@@ -0,0 +1,210 @@
+//! This module provides tile maps, grids of tiles cut out of a single image, as used for the
+//! levels of 2D games.
+
+use crate::{DrawCall, Image, ScreenContextManager};
+
+/// An image cut into equally sized tiles, numbered left to right and then top to bottom starting
+/// at 0. Leftover pixels at the right and bottom don't make up a tile.
+#[derive(Clone, Debug)]
+pub struct SpriteSheet {
+    image: Image,
+    tile_width: u32,
+    tile_height: u32,
+}
+
+impl SpriteSheet {
+    /// Cuts `image` into tiles of `tile_width`×`tile_height` pixels.
+    pub fn new(image: Image, tile_width: u32, tile_height: u32) -> SpriteSheet {
+        SpriteSheet {
+            image,
+            tile_width,
+            tile_height,
+        }
+    }
+
+    /// Returns the width and height of a single tile.
+    pub fn get_tile_size(&self) -> (u32, u32) {
+        (self.tile_width, self.tile_height)
+    }
+
+    fn columns(&self) -> u32 {
+        self.image
+            .get_width()
+            .checked_div(self.tile_width)
+            .unwrap_or(0)
+    }
+
+    /// Returns how many tiles the sheet holds.
+    pub fn get_tile_count(&self) -> u32 {
+        let rows = self
+            .image
+            .get_height()
+            .checked_div(self.tile_height)
+            .unwrap_or(0);
+        self.columns() * rows
+    }
+
+    /// Copies tile `index` into an image of its own, or returns `None` if there is no such tile.
+    pub fn get_tile_image(&self, index: u32) -> Option<Image> {
+        self.tile(index).map(|region| self.image.crop(region))
+    }
+
+    /// Returns the part of the image holding tile `index` as `(x, y, width, height)`, or `None`
+    /// if there is no such tile.
+    fn tile(&self, index: u32) -> Option<(u32, u32, u32, u32)> {
+        if index >= self.get_tile_count() {
+            return None;
+        }
+        let columns = self.columns();
+        Some((
+            index % columns * self.tile_width,
+            index / columns * self.tile_height,
+            self.tile_width,
+            self.tile_height,
+        ))
+    }
+}
+
+/// A grid of tiles from a [`SpriteSheet`], drawn with
+/// [`draw_tilemap`](ScreenContextManager::draw_tilemap). Cells start out empty.
+#[derive(Clone, Debug)]
+pub struct TileMap {
+    sheet: SpriteSheet,
+    columns: u32,
+    rows: u32,
+    tiles: Vec<Option<u32>>,
+}
+
+impl TileMap {
+    /// Creates an empty map of `columns`×`rows` cells, filled with tiles from `sheet`.
+    pub fn new(sheet: SpriteSheet, columns: u32, rows: u32) -> TileMap {
+        TileMap {
+            sheet,
+            columns,
+            rows,
+            tiles: vec![None; columns as usize * rows as usize],
+        }
+    }
+
+    /// Returns the number of columns and rows of the map.
+    pub fn get_size(&self) -> (u32, u32) {
+        (self.columns, self.rows)
+    }
+
+    /// Returns the sheet the tiles come from.
+    pub fn get_sheet(&self) -> &SpriteSheet {
+        &self.sheet
+    }
+
+    /// Puts the tile with the given index of the sheet into the cell at (`column`, `row`), or
+    /// empties it with `None`. Cells outside the map are ignored.
+    pub fn set_tile(&mut self, column: u32, row: u32, tile: Option<u32>) {
+        if column < self.columns && row < self.rows {
+            self.tiles[row as usize * self.columns as usize + column as usize] = tile;
+        }
+    }
+
+    /// Returns the tile in the cell at (`column`, `row`), `None` for empty cells and cells
+    /// outside the map.
+    pub fn get_tile(&self, column: u32, row: u32) -> Option<u32> {
+        if column < self.columns && row < self.rows {
+            self.tiles[row as usize * self.columns as usize + column as usize]
+        } else {
+            None
+        }
+    }
+}
+
+impl ScreenContextManager {
+    /// Draws `map` scrolled so that its pixel (`camera_x`, `camera_y`) ends up at the top-left
+    /// corner of the framebuffer. Only the tiles that can be seen through the clip bounds are
+    /// drawn, so maps can be much larger than the screen. The map then goes through the screen's
+    /// [camera](ScreenContextManager::set_camera) like images do. Tiles are blended like
+    /// [`blit_image`](ScreenContextManager::blit_image), and empty cells or indices past the end
+    /// of the sheet are skipped.
+    pub fn draw_tilemap(&mut self, map: &TileMap, (camera_x, camera_y): (i32, i32)) {
+        self.report(|| DrawCall::TileMap {
+            scroll: (camera_x, camera_y),
+        });
+        let (tile_width, tile_height) = map.sheet.get_tile_size();
+        if tile_width == 0 || tile_height == 0 {
+            return;
+        }
+        // The visible pixels before the screen's camera moves them onto the framebuffer, padded by
+        // a pixel for those only partly visible
+        let clip = self.clip_bounds();
+        let (left, top) = self
+            .camera
+            .screen_to_world((clip.left as f32, clip.top as f32));
+        let (right, bottom) = self
+            .camera
+            .screen_to_world((clip.right as f32, clip.bottom as f32));
+        let (left, right) = (left.min(right).floor() - 1.0, left.max(right).ceil() + 1.0);
+        let (top, bottom) = (top.min(bottom).floor() - 1.0, top.max(bottom).ceil() + 1.0);
+        // The range of cells covering the visible pixels along one axis
+        let visible = |from: f32, to: f32, camera: i32, size: u32, count: u32| {
+            let first = (from as i64 + camera as i64).div_euclid(size as i64);
+            let last = (to as i64 + camera as i64 - 1).div_euclid(size as i64);
+            first.max(0)..(last + 1).min(count as i64)
+        };
+        let columns = visible(left, right, camera_x, tile_width, map.columns);
+        let rows = visible(top, bottom, camera_y, tile_height, map.rows);
+
+        for row in rows {
+            for column in columns.clone() {
+                let tile = map.get_tile(column as u32, row as u32);
+                if let Some(region) = tile.and_then(|tile| map.sheet.tile(tile)) {
+                    let x = column * tile_width as i64 - camera_x as i64;
+                    let y = row * tile_height as i64 - camera_y as i64;
+                    self.blit_image_region(&map.sheet.image, region, x as i32, y as i32);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet(width: u32, height: u32, tile_width: u32, tile_height: u32) -> SpriteSheet {
+        let image = Image::from_pixels(image::RgbaImage::new(width, height));
+        SpriteSheet::new(image, tile_width, tile_height)
+    }
+
+    #[test]
+    fn tiles_are_numbered_row_by_row() {
+        let sheet = sheet(48, 32, 16, 16);
+        assert_eq!(sheet.get_tile_count(), 6);
+        assert_eq!(sheet.tile(0), Some((0, 0, 16, 16)));
+        assert_eq!(sheet.tile(2), Some((32, 0, 16, 16)));
+        assert_eq!(sheet.tile(4), Some((16, 16, 16, 16)));
+        assert_eq!(sheet.tile(6), None);
+    }
+
+    #[test]
+    fn leftover_pixels_make_no_tile() {
+        let sheet = sheet(40, 20, 16, 16);
+        assert_eq!(sheet.get_tile_count(), 2);
+        assert_eq!(sheet.tile(1), Some((16, 0, 16, 16)));
+        assert_eq!(sheet.tile(2), None);
+    }
+
+    #[test]
+    fn zero_sized_tiles_make_an_empty_sheet() {
+        let sheet = sheet(16, 16, 0, 0);
+        assert_eq!(sheet.get_tile_count(), 0);
+        assert_eq!(sheet.tile(0), None);
+    }
+
+    #[test]
+    fn cells_are_set_and_read_back() {
+        let mut map = TileMap::new(sheet(32, 32, 16, 16), 3, 2);
+        map.set_tile(2, 1, Some(3));
+        map.set_tile(3, 0, Some(1));
+        assert_eq!(map.get_tile(2, 1), Some(3));
+        assert_eq!(map.get_tile(0, 1), None);
+        assert_eq!(map.get_tile(3, 0), None);
+        assert_eq!(map.tiles.iter().filter(|tile| tile.is_some()).count(), 1);
+    }
+}
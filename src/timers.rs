@@ -0,0 +1,176 @@
+//! This module schedules closures to run after a delay or at a fixed interval, dispatched from
+//! the frame loop.
+
+use crate::ScreenContextManager;
+use std::time::{Duration, Instant};
+
+/// Identifies a timer, to cancel it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+enum Callback {
+    Once(Box<dyn FnOnce(&mut ScreenContextManager)>),
+    Repeating(Box<dyn FnMut(&mut ScreenContextManager)>, Duration),
+}
+
+struct Timer {
+    id: TimerId,
+    deadline: Instant,
+    callback: Callback,
+}
+
+/// The scheduled timers.
+#[derive(Default)]
+pub(crate) struct Timers {
+    timers: Vec<Timer>,
+    next_id: u64,
+    // The repeating timer whose closure is running, and whether it cancelled itself
+    running: Option<(TimerId, bool)>,
+}
+
+impl Timers {
+    fn schedule(&mut self, deadline: Instant, callback: Callback) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        self.timers.push(Timer {
+            id,
+            deadline,
+            callback,
+        });
+        id
+    }
+
+    /// Returns the timers due at `now`, earliest deadline first and in the order they were
+    /// scheduled for equal deadlines.
+    fn due(&self, now: Instant) -> Vec<TimerId> {
+        let mut due: Vec<(Instant, TimerId)> = self
+            .timers
+            .iter()
+            .filter(|timer| timer.deadline <= now)
+            .map(|timer| (timer.deadline, timer.id))
+            .collect();
+        due.sort_by_key(|&(deadline, _)| deadline);
+        due.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Returns when the next timer is due, if any is scheduled.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+}
+
+/// When a repeating timer that was due at `deadline` and ran at `now` runs next: an interval
+/// after whichever is later, so that missed intervals don't pile up.
+fn next_deadline(deadline: Instant, interval: Duration, now: Instant) -> Instant {
+    (deadline + interval).max(now + interval)
+}
+
+impl ScreenContextManager {
+    /// Runs `callback` once, the first time timers are dispatched after `delay` has passed.
+    pub fn after<F>(&mut self, delay: Duration, callback: F) -> TimerId
+    where
+        F: FnOnce(&mut ScreenContextManager) + 'static,
+    {
+        let deadline = Instant::now() + delay;
+        self.timers
+            .schedule(deadline, Callback::Once(Box::new(callback)))
+    }
+
+    /// Runs `callback` every `interval` until the timer is cancelled. Late dispatches don't
+    /// pile up: a timer that missed several intervals runs once, then an interval later.
+    pub fn every<F>(&mut self, interval: Duration, callback: F) -> TimerId
+    where
+        F: FnMut(&mut ScreenContextManager) + 'static,
+    {
+        let deadline = Instant::now() + interval;
+        let callback = Callback::Repeating(Box::new(callback), interval);
+        self.timers.schedule(deadline, callback)
+    }
+
+    /// Cancels a timer, including from within its own closure. Timers that already ran out are
+    /// ignored.
+    pub fn cancel_timer(&mut self, timer: TimerId) {
+        self.timers.timers.retain(|scheduled| scheduled.id != timer);
+        if let Some((running, cancelled)) = self.timers.running.as_mut() {
+            if *running == timer {
+                *cancelled = true;
+            }
+        }
+    }
+
+    /// Runs the closures of all the timers that are due, earliest first.
+    /// [`run`](ScreenContextManager::run) calls this before every frame, and wakes up for timers
+    /// when waiting for events; applications with their own loop call it themselves.
+    pub fn run_timers(&mut self) {
+        let now = Instant::now();
+        // Every due timer runs once, even if a closure schedules timers that are due right away
+        for id in self.timers.due(now) {
+            let index = match self.timers.timers.iter().position(|timer| timer.id == id) {
+                Some(index) => index,
+                // Cancelled by an earlier closure
+                None => continue,
+            };
+            let timer = self.timers.timers.remove(index);
+            match timer.callback {
+                Callback::Once(callback) => callback(self),
+                Callback::Repeating(mut callback, interval) => {
+                    self.timers.running = Some((id, false));
+                    callback(self);
+                    if let Some((_, false)) = self.timers.running.take() {
+                        let deadline = next_deadline(timer.deadline, interval, now);
+                        self.timers.timers.push(Timer {
+                            id,
+                            deadline,
+                            callback: Callback::Repeating(callback, interval),
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn once() -> Callback {
+        Callback::Once(Box::new(|_| {}))
+    }
+
+    #[test]
+    fn due_timers_run_earliest_first() {
+        let now = Instant::now();
+        let mut timers = Timers::default();
+        let late = timers.schedule(now - MS, once());
+        let early = timers.schedule(now - 3 * MS, once());
+        let future = timers.schedule(now + MS, once());
+        let tied = timers.schedule(now - MS, once());
+        assert_eq!(timers.due(now), vec![early, late, tied]);
+        assert_eq!(timers.next_deadline(), Some(now - 3 * MS));
+        assert!(!timers.due(now).contains(&future));
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        let now = Instant::now();
+        let mut timers = Timers::default();
+        let first = timers.schedule(now, once());
+        let second = timers.schedule(now, once());
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn repeating_timers_dont_pile_up() {
+        let start = Instant::now();
+        let interval = 10 * MS;
+        assert_eq!(next_deadline(start, interval, start), start + interval);
+        assert_eq!(next_deadline(start, interval, start + MS), start + 11 * MS);
+        assert_eq!(
+            next_deadline(start, interval, start + 35 * MS),
+            start + 45 * MS
+        );
+    }
+}
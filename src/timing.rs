@@ -0,0 +1,181 @@
+//! This module keeps track of frame timing, pacing presentation to a target frame rate.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long before a frame deadline sleeping stops and spin-waiting takes over, since sleeps
+/// routinely overshoot by about a millisecond.
+const SPIN_MARGIN: Duration = Duration::from_micros(1500);
+
+/// Waits out the remainder of every frame so that frames come at a steady rate.
+pub struct FramePacer {
+    fps: u32,
+    interval: Duration,
+    next_frame: Option<Instant>,
+}
+
+impl FramePacer {
+    pub fn new(fps: u32) -> FramePacer {
+        FramePacer {
+            fps,
+            // Rates past a billion frames per second would round down to no interval at all
+            interval: (Duration::from_secs(1) / fps.max(1)).max(Duration::from_nanos(1)),
+            next_frame: None,
+        }
+    }
+
+    /// Returns the frame rate the pacer was created with.
+    pub fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    /// Blocks until the current frame's time is up.
+    pub fn wait(&mut self) {
+        let now = Instant::now();
+        let deadline = match self.advance(now) {
+            Some(deadline) => deadline,
+            None => return,
+        };
+
+        if let Some(sleep) = (deadline - now).checked_sub(SPIN_MARGIN) {
+            std::thread::sleep(sleep);
+        }
+        while Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+    }
+
+    /// Moves on to the next frame at `now`, returning the deadline to wait for, or `None` if
+    /// the frame can go right away.
+    fn advance(&mut self, now: Instant) -> Option<Instant> {
+        match self.next_frame {
+            Some(deadline) if deadline > now => {
+                self.next_frame = Some(deadline + self.interval);
+                Some(deadline)
+            }
+            // First frame, or too far behind to catch up
+            _ => {
+                self.next_frame = Some(now + self.interval);
+                None
+            }
+        }
+    }
+}
+
+/// Amount of frames the statistics are computed over
+const STATS_WINDOW: usize = 120;
+
+/// Frame time statistics over the last couple of seconds, see
+/// [`get_frame_stats`](crate::ScreenContextManager::get_frame_stats).
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    /// Time between the last two presented frames.
+    pub last: Duration,
+    /// Average frame time.
+    pub average: Duration,
+    /// 95% of the frames took at most this long, a good measure of stutter.
+    pub percentile_95: Duration,
+    /// Frames per second according to the average frame time.
+    pub fps: f64,
+}
+
+/// Records the time between presented frames.
+#[derive(Default)]
+pub struct FrameTimer {
+    last_frame: Option<Instant>,
+    frame_times: VecDeque<Duration>,
+}
+
+impl FrameTimer {
+    /// Marks that a frame was just presented.
+    pub fn record(&mut self) {
+        let now = Instant::now();
+        if let Some(last_frame) = self.last_frame {
+            self.push(now - last_frame);
+        }
+        self.last_frame = Some(now);
+    }
+
+    fn push(&mut self, frame_time: Duration) {
+        if self.frame_times.len() == STATS_WINDOW {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+    }
+
+    pub fn stats(&self) -> FrameStats {
+        let last = match self.frame_times.back() {
+            Some(&last) => last,
+            None => return FrameStats::default(),
+        };
+
+        let total: Duration = self.frame_times.iter().sum();
+        let average = total / self.frame_times.len() as u32;
+
+        let mut sorted: Vec<Duration> = self.frame_times.iter().copied().collect();
+        sorted.sort_unstable();
+        let percentile_95 = sorted[(sorted.len() * 95 / 100).min(sorted.len() - 1)];
+
+        FrameStats {
+            last,
+            average,
+            percentile_95,
+            fps: if average.is_zero() {
+                0.0
+            } else {
+                1.0 / average.as_secs_f64()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn interval_follows_the_rate() {
+        assert_eq!(FramePacer::new(50).interval, 20 * MS);
+        assert_eq!(FramePacer::new(0).interval, Duration::from_secs(1));
+        assert_eq!(FramePacer::new(u32::MAX).interval, Duration::from_nanos(1));
+    }
+
+    #[test]
+    fn frames_are_paced_from_the_previous_deadline() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(50);
+        assert_eq!(pacer.advance(start), None);
+        // A frame that took 5ms waits out the remaining 15ms
+        assert_eq!(pacer.advance(start + 5 * MS), Some(start + 20 * MS));
+        // Deadlines don't drift with how late the wait ends
+        assert_eq!(pacer.advance(start + 21 * MS), Some(start + 40 * MS));
+    }
+
+    #[test]
+    fn late_frames_start_over() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(50);
+        pacer.advance(start);
+        assert_eq!(pacer.advance(start + 100 * MS), None);
+        assert_eq!(pacer.advance(start + 105 * MS), Some(start + 120 * MS));
+    }
+
+    #[test]
+    fn stats_cover_the_last_frames() {
+        let mut timer = FrameTimer::default();
+        assert_eq!(timer.stats(), FrameStats::default());
+        for _ in 0..STATS_WINDOW {
+            timer.push(100 * MS);
+        }
+        for i in 0..STATS_WINDOW as u64 {
+            timer.push(if i < 114 { 10 * MS } else { 40 * MS });
+        }
+        let stats = timer.stats();
+        assert_eq!(stats.last, 40 * MS);
+        assert_eq!(stats.average, 11500 * Duration::from_micros(1));
+        assert_eq!(stats.percentile_95, 40 * MS);
+        assert!((stats.fps - 1.0 / 0.0115).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,110 @@
+//! This module renders text with TrueType fonts through SDL2_ttf, blending the antialiased glyphs
+//! into the framebuffer.
+
+use crate::{Color, DrawCall, FontError, Image, ScreenContextManager};
+use sdl2::{pixels::Color as SdlColor, ttf::Sdl2TtfContext};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// SDL2_ttf, initialized on the first font load and kept for the rest of the program so that
+/// fonts don't have to borrow it.
+static TTF: OnceLock<Sdl2TtfContext> = OnceLock::new();
+
+/// A TrueType font loaded at a given size, see [`load_font`](ScreenContextManager::load_font).
+pub struct Font {
+    font: sdl2::ttf::Font<'static, 'static>,
+}
+
+impl Font {
+    /// Returns the width and height in pixels that
+    /// [`draw_text_ttf`](ScreenContextManager::draw_text_ttf) covers when drawing `text` with this
+    /// font, to center, align or wrap it.
+    pub fn measure_text(&self, text: &str) -> Result<(u32, u32), FontError> {
+        if text.is_empty() {
+            return Ok((0, self.font.height().max(0) as u32));
+        }
+        Ok(self.font.size_of(text).map_err(|e| e.to_string())?)
+    }
+}
+
+impl ScreenContextManager {
+    /// Loads the TrueType (or OpenType) font at `path`, rendered `size` points high.
+    pub fn load_font<P: AsRef<Path>>(&self, path: P, size: u16) -> Result<Font, FontError> {
+        let ttf = match TTF.get() {
+            Some(ttf) => ttf,
+            None => {
+                let ttf = sdl2::ttf::init().map_err(|e| e.to_string())?;
+                TTF.get_or_init(|| ttf)
+            }
+        };
+        let font = ttf.load_font(path, size)?;
+        Ok(Font { font })
+    }
+
+    /// Draws `text` with `font` in the current color, its top-left corner at (`x`, `y`). The
+    /// glyphs are antialiased, their edges blended with what is already in the framebuffer, and
+    /// anything outside the framebuffer is clipped.
+    pub fn draw_text_ttf(
+        &mut self,
+        font: &Font,
+        x: i32,
+        y: i32,
+        text: &str,
+    ) -> Result<(), FontError> {
+        self.report(|| DrawCall::Text {
+            x,
+            y,
+            text: text.to_string(),
+        });
+        if text.is_empty() {
+            return Ok(());
+        }
+        // Rendered in white so that the alpha channel alone carries the coverage
+        let surface = font
+            .font
+            .render(text)
+            .blended(SdlColor::WHITE)
+            .map_err(|e| e.to_string())?;
+        let (width, height, pitch) = (surface.width(), surface.height(), surface.pitch());
+        let pixels = surface
+            .without_lock()
+            .ok_or("the rendered text can't be read")?;
+        let coverage = |column: u32, row: u32| {
+            let offset = (row * pitch + column * 4) as usize;
+            let bytes = &pixels[offset..offset + 4];
+            let argb = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            (argb >> 24) as u8
+        };
+
+        if !self.camera.is_identity() {
+            // Drawn through an image so that the glyphs are scaled like everything else
+            let Color { r, g, b } = self.color;
+            let pixels = image::RgbaImage::from_fn(width, height, |column, row| {
+                image::Rgba([r, g, b, coverage(column, row)])
+            });
+            self.blit_image(&Image::from_pixels(pixels), x, y);
+            return Ok(());
+        }
+        let clip = self.clip_bounds();
+        for row in 0..height {
+            let py = y + row as i32;
+            if !clip.contains_row(py as i64) {
+                continue;
+            }
+            for column in 0..width {
+                let px = x + column as i32;
+                if !clip.contains(px as i64, py as i64) {
+                    continue;
+                }
+                let alpha = coverage(column, row) as f32 / 255.0;
+                if alpha > 0.0 {
+                    let i = (py as u32 * self.width + px as u32) as usize;
+                    self.framebuffer[i] =
+                        self.color_space
+                            .blend(self.framebuffer[i], self.color, alpha);
+                }
+            }
+        }
+        Ok(())
+    }
+}
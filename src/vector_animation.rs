@@ -0,0 +1,275 @@
+//! This module provides a tiny keyframe animation layer for vector shapes, enough for animated
+//! logos and loading indicators drawn with the crate's own primitives.
+
+use crate::primitives::Point;
+use crate::{FillRule, ScreenContextManager, StrokeStyle};
+use std::time::Duration;
+
+/// A value that can be interpolated between two keyframes.
+pub trait Animatable: Copy {
+    /// Returns the value `t` of the way from `self` to `other`, `t` being in the range [0, 1].
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Animatable for f32 {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Animatable for (f32, f32) {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        (self.0.lerp(other.0, t), self.1.lerp(other.1, t))
+    }
+}
+
+impl Animatable for (f32, f32, f32) {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        (
+            self.0.lerp(other.0, t),
+            self.1.lerp(other.1, t),
+            self.2.lerp(other.2, t),
+        )
+    }
+}
+
+/// How a value moves from one keyframe to the next.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Easing {
+    /// At constant speed.
+    Linear,
+    /// Starting slowly and speeding up.
+    EaseIn,
+    /// Starting fast and slowing down.
+    EaseOut,
+    /// Slow at both ends.
+    EaseInOut,
+    /// Jumping to the next value once its keyframe is reached.
+    Step,
+}
+
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+            Easing::Step => 0.0,
+        }
+    }
+}
+
+/// A value that changes over time, given by keyframes with easing in between.
+#[derive(Clone, Debug)]
+pub struct Keyframes<T> {
+    /// Time in seconds, value, and easing used to get there from the previous keyframe
+    keys: Vec<(f32, T, Easing)>,
+    looping: bool,
+}
+
+impl<T: Animatable> Keyframes<T> {
+    /// Creates a track that holds `value` until more keyframes are added.
+    pub fn new(value: T) -> Keyframes<T> {
+        Keyframes {
+            keys: vec![(0.0, value, Easing::Linear)],
+            looping: false,
+        }
+    }
+
+    /// Adds a keyframe reaching `value` at `seconds`, moving there with `easing`. Keyframes are
+    /// kept sorted by time.
+    pub fn key(mut self, seconds: f32, value: T, easing: Easing) -> Keyframes<T> {
+        let index = self.keys.partition_point(|&(time, _, _)| time <= seconds);
+        self.keys.insert(index, (seconds, value, easing));
+        self
+    }
+
+    /// Makes the track start over once its last keyframe is reached.
+    pub fn looping(mut self, looping: bool) -> Keyframes<T> {
+        self.looping = looping;
+        self
+    }
+
+    /// Time of the last keyframe, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.keys.last().map_or(0.0, |&(time, _, _)| time)
+    }
+
+    /// Returns the value of the track at the given time in seconds.
+    pub fn sample(&self, seconds: f32) -> T {
+        let duration = self.duration();
+        let seconds = if self.looping && duration > 0.0 {
+            seconds.rem_euclid(duration)
+        } else {
+            seconds
+        };
+
+        let next = self.keys.partition_point(|&(time, _, _)| time <= seconds);
+        if next == 0 {
+            return self.keys[0].1;
+        }
+        if next == self.keys.len() {
+            return self.keys[next - 1].1;
+        }
+
+        let (from_time, from, _) = self.keys[next - 1];
+        let (to_time, to, easing) = self.keys[next];
+        let t = (seconds - from_time) / (to_time - from_time);
+        from.lerp(to, easing.apply(t))
+    }
+}
+
+/// Geometry of an animated shape, in coordinates relative to its position.
+#[derive(Clone, Debug)]
+pub enum ShapeKind {
+    /// A filled circle.
+    Circle { radius: f32 },
+    /// A filled polygon.
+    Polygon(Vec<(f32, f32)>),
+    /// A stroked open line.
+    Polyline(Vec<(f32, f32)>, StrokeStyle),
+}
+
+/// A shape whose placement and color are animated.
+#[derive(Clone, Debug)]
+pub struct AnimatedShape {
+    pub kind: ShapeKind,
+    /// Where the shape's origin is on the framebuffer.
+    pub position: Keyframes<(f32, f32)>,
+    /// Rotation around the shape's origin, in radians.
+    pub rotation: Keyframes<f32>,
+    pub scale: Keyframes<f32>,
+    /// RGB color with components in the range [0, 1].
+    pub color: Keyframes<(f32, f32, f32)>,
+}
+
+impl AnimatedShape {
+    /// Creates a white shape at `position` that doesn't move until keyframes are added to its
+    /// tracks.
+    pub fn new(kind: ShapeKind, position: (f32, f32)) -> AnimatedShape {
+        AnimatedShape {
+            kind,
+            position: Keyframes::new(position),
+            rotation: Keyframes::new(0.0),
+            scale: Keyframes::new(1.0),
+            color: Keyframes::new((1.0, 1.0, 1.0)),
+        }
+    }
+}
+
+/// A group of animated shapes drawn together, in order.
+#[derive(Clone, Debug, Default)]
+pub struct VectorAnimation {
+    pub shapes: Vec<AnimatedShape>,
+}
+
+impl VectorAnimation {
+    pub fn new() -> VectorAnimation {
+        VectorAnimation::default()
+    }
+
+    pub fn shape(mut self, shape: AnimatedShape) -> VectorAnimation {
+        self.shapes.push(shape);
+        self
+    }
+}
+
+impl ScreenContextManager {
+    /// Draws `animation` as it is at the current time of the animation clock. The current color
+    /// is left as the color of the last shape drawn.
+    pub fn draw_vector_animation(&mut self, animation: &VectorAnimation) {
+        let seconds = self.animation_time.as_secs_f32();
+
+        for shape in &animation.shapes {
+            let (x, y) = shape.position.sample(seconds);
+            let (sin, cos) = shape.rotation.sample(seconds).sin_cos();
+            let scale = shape.scale.sample(seconds);
+            let transform = |&(px, py): &Point| {
+                let (px, py) = (px * scale, py * scale);
+                (x + px * cos - py * sin, y + px * sin + py * cos)
+            };
+
+            let (r, g, b) = shape.color.sample(seconds);
+            self.set_color(r, g, b);
+
+            match &shape.kind {
+                ShapeKind::Circle { radius } => self.fill_disc((x, y), radius * scale),
+                ShapeKind::Polygon(points) => {
+                    let points: Vec<Point> = points.iter().map(transform).collect();
+                    self.fill_polygon(&points, FillRule::NonZero);
+                }
+                ShapeKind::Polyline(points, style) => {
+                    let points: Vec<Point> = points.iter().map(transform).collect();
+                    let style = StrokeStyle {
+                        width: style.width * scale,
+                        ..*style
+                    };
+                    self.stroke_polyline(&points, &style);
+                }
+            }
+        }
+    }
+
+    /// Returns the time of the animation clock, which [`run`](ScreenContextManager::run)
+    /// advances by the real time between frames.
+    pub fn get_animation_time(&self) -> Duration {
+        self.animation_time
+    }
+
+    /// Moves the animation clock forward, for loops that don't use
+    /// [`run`](ScreenContextManager::run).
+    pub fn advance_animation_clock(&mut self, elapsed: Duration) {
+        self.animation_time += elapsed;
+    }
+
+    /// Sets the animation clock to the given time, e.g. to restart animations.
+    pub fn set_animation_time(&mut self, time: Duration) {
+        self.animation_time = time;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_held_outside_the_keyframes() {
+        let track = Keyframes::new(1.0).key(1.0, 3.0, Easing::Linear);
+        assert_eq!(track.sample(-1.0), 1.0);
+        assert_eq!(track.sample(0.0), 1.0);
+        assert_eq!(track.sample(1.0), 3.0);
+        assert_eq!(track.sample(5.0), 3.0);
+    }
+
+    #[test]
+    fn easing_shapes_the_way_between_keyframes() {
+        let sample = |easing| Keyframes::new(0.0).key(2.0, 4.0, easing).sample(0.5);
+        assert_eq!(sample(Easing::Linear), 1.0);
+        assert_eq!(sample(Easing::EaseIn), 0.25);
+        assert_eq!(sample(Easing::EaseOut), 1.75);
+        assert_eq!(sample(Easing::EaseInOut), 0.625);
+        assert_eq!(sample(Easing::Step), 0.0);
+    }
+
+    #[test]
+    fn keyframes_are_sorted_by_time() {
+        let track = Keyframes::new((0.0, 0.0))
+            .key(2.0, (2.0, -2.0), Easing::Linear)
+            .key(1.0, (4.0, -4.0), Easing::Linear);
+        assert_eq!(track.duration(), 2.0);
+        assert_eq!(track.sample(0.5), (2.0, -2.0));
+        assert_eq!(track.sample(1.5), (3.0, -3.0));
+    }
+
+    #[test]
+    fn looping_tracks_start_over() {
+        let track = Keyframes::new(0.0)
+            .key(2.0, 2.0, Easing::Linear)
+            .looping(true);
+        assert_eq!(track.sample(2.5), 0.5);
+        assert_eq!(track.sample(-0.5), 1.5);
+        let still = Keyframes::new(7.0).looping(true);
+        assert_eq!(still.sample(3.0), 7.0);
+    }
+}
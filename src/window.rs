@@ -0,0 +1,194 @@
+//! This module exposes control over the window the framebuffer is presented on.
+
+use crate::{ScreenContextManager, WindowError};
+use sdl2::{
+    event::Event, keyboard::Keycode, keyboard::Mod, pixels::PixelFormatEnum, surface::Surface,
+    video::FullscreenType, video::Window, video::WindowPos,
+};
+use std::path::Path;
+
+/// Fullscreen state of the window.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FullscreenMode {
+    /// A regular window.
+    #[default]
+    Off,
+    /// Borderless fullscreen on the monitor the window is currently on, without changing the
+    /// display mode. Combined with [`ScaleMode::Center`](crate::ScaleMode::Center) the
+    /// framebuffer is kept at its native size, which is what pixel-perfect applications want.
+    Desktop,
+    /// Exclusive fullscreen, switching the display mode to the window's size.
+    Exclusive,
+}
+
+impl From<FullscreenMode> for FullscreenType {
+    fn from(mode: FullscreenMode) -> Self {
+        match mode {
+            FullscreenMode::Off => FullscreenType::Off,
+            FullscreenMode::Desktop => FullscreenType::Desktop,
+            FullscreenMode::Exclusive => FullscreenType::True,
+        }
+    }
+}
+
+impl ScreenContextManager {
+    /// Switches the window between windowed and fullscreen modes.
+    pub fn set_fullscreen(&mut self, mode: FullscreenMode) -> Result<(), WindowError> {
+        Ok(self
+            .output
+            .canvas
+            .window_mut()
+            .set_fullscreen(mode.into())?)
+    }
+
+    /// Returns the current fullscreen state of the window.
+    pub fn get_fullscreen(&self) -> FullscreenMode {
+        match self.output.canvas.window().fullscreen_state() {
+            FullscreenType::Off => FullscreenMode::Off,
+            FullscreenType::Desktop => FullscreenMode::Desktop,
+            FullscreenType::True => FullscreenMode::Exclusive,
+        }
+    }
+
+    /// Returns SDL's id of the window, which identifies it among the windows of an
+    /// [`SdlContext`](crate::SdlContext).
+    pub fn get_window_id(&self) -> u32 {
+        self.window_id
+    }
+
+    /// Changes the title of the window.
+    pub fn set_title(&mut self, title: &str) -> Result<(), WindowError> {
+        Ok(self.output.canvas.window_mut().set_title(title)?)
+    }
+
+    /// Returns the current title of the window.
+    pub fn get_title(&self) -> &str {
+        self.output.canvas.window().title()
+    }
+
+    /// Moves the window so its top-left corner is at the given desktop coordinates. Every display
+    /// occupies its own region of the desktop, so this also picks the display the window is on.
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.output
+            .canvas
+            .window_mut()
+            .set_position(WindowPos::Positioned(x), WindowPos::Positioned(y));
+    }
+
+    /// Moves the window to the center of the display it is currently on.
+    pub fn center_on_screen(&mut self) -> Result<(), WindowError> {
+        let window = self.output.canvas.window_mut();
+        let display = window.subsystem().display_bounds(window.display_index()?)?;
+        let (width, height) = window.size();
+        window.set_position(
+            WindowPos::Positioned(display.x() + (display.width() as i32 - width as i32) / 2),
+            WindowPos::Positioned(display.y() + (display.height() as i32 - height as i32) / 2),
+        );
+        Ok(())
+    }
+
+    /// Returns the desktop coordinates of the window's top-left corner.
+    pub fn get_position(&self) -> (i32, i32) {
+        self.output.canvas.window().position()
+    }
+
+    /// Makes the window visible, e.g. after creating it hidden with
+    /// [`WindowOptions::hidden`](crate::WindowOptions::hidden) and drawing the first frame.
+    pub fn show(&mut self) {
+        self.output.canvas.window_mut().show();
+    }
+
+    /// Hides the window. The framebuffer can still be drawn to and presented.
+    pub fn hide(&mut self) {
+        self.output.canvas.window_mut().hide();
+    }
+
+    /// Minimizes the window.
+    pub fn minimize(&mut self) {
+        self.output.canvas.window_mut().minimize();
+    }
+
+    /// Maximizes the window.
+    pub fn maximize(&mut self) {
+        self.output.canvas.window_mut().maximize();
+    }
+
+    /// Restores a minimized or maximized window to its previous size and position.
+    pub fn restore(&mut self) {
+        self.output.canvas.window_mut().restore();
+    }
+
+    /// Sets the opacity of the whole window, from 0 (fully transparent) to 1 (opaque). Fails on
+    /// platforms that don't support translucent windows.
+    pub fn set_opacity(&mut self, opacity: f32) -> Result<(), WindowError> {
+        Ok(self
+            .output
+            .canvas
+            .window_mut()
+            .set_opacity(opacity.clamp(0.0, 1.0))?)
+    }
+
+    /// Prevents the user from shrinking the window below the given size.
+    pub fn set_min_size(&mut self, width: u32, height: u32) -> Result<(), WindowError> {
+        Ok(self
+            .output
+            .canvas
+            .window_mut()
+            .set_minimum_size(width, height)
+            .map_err(|e| e.to_string())?)
+    }
+
+    /// Prevents the user from growing the window beyond the given size.
+    pub fn set_max_size(&mut self, width: u32, height: u32) -> Result<(), WindowError> {
+        Ok(self
+            .output
+            .canvas
+            .window_mut()
+            .set_maximum_size(width, height)
+            .map_err(|e| e.to_string())?)
+    }
+
+    /// Loads an image (in any format the `image` crate can decode, e.g. PNG) and sets it as the
+    /// window's icon.
+    pub fn set_icon<P: AsRef<Path>>(&mut self, path: P) -> Result<(), WindowError> {
+        let mut icon = image::open(path)?.to_rgba8();
+        let (width, height) = icon.dimensions();
+        let surface =
+            Surface::from_data(&mut icon, width, height, width * 4, PixelFormatEnum::RGBA32)?;
+        self.output.canvas.window_mut().set_icon(surface);
+        Ok(())
+    }
+
+    /// Makes Alt+Enter toggle the window between windowed and the given fullscreen mode while
+    /// events are being read, or disables the shortcut with `None`. The key presses handling the
+    /// shortcut are not delivered as events.
+    pub fn set_alt_enter_fullscreen(&mut self, mode: Option<FullscreenMode>) {
+        self.alt_enter_fullscreen = mode;
+    }
+}
+
+/// Toggles fullscreen if `event` is an Alt+Enter press, returning whether it was handled.
+pub fn handle_alt_enter(window: &mut Window, mode: Option<FullscreenMode>, event: &Event) -> bool {
+    let mode = match (mode, event) {
+        (
+            Some(mode),
+            Event::KeyDown {
+                keycode: Some(Keycode::Return),
+                keymod,
+                repeat: false,
+                ..
+            },
+        ) if keymod.intersects(Mod::LALTMOD | Mod::RALTMOD) => mode,
+        _ => return false,
+    };
+
+    let target = if window.fullscreen_state() == FullscreenType::Off {
+        mode.into()
+    } else {
+        FullscreenType::Off
+    };
+    // There's nobody to report a failure to in the middle of reading events, and staying in the
+    // current mode is a fine outcome
+    let _ = window.set_fullscreen(target);
+    true
+}